@@ -1,26 +1,35 @@
-use lang_engine::Engine;
+use std::fs::File;
+
+use lang_engine::{transpiler::{source_map::SourceMap, Target, TranspilerOptions, TranspilerTarget}, Engine};
 
 #[macro_use] extern crate log;
 
 fn main() {
     pretty_env_logger::formatted_builder()
-        .target(pretty_env_logger::env_logger::Target::Stdout)
+        .target(pretty_env_logger::env_logger::Target::Stderr)
         .format_module_path(true)
         .filter_level(log::LevelFilter::Trace)
         .init();
 
     debug!("initialized logger");
 
-    let mut engine = Engine::default();
+    let mut args: Vec<String> = std::env::args().collect();
 
+    // `--debug` builds a script that traces the lines of the source it runs
+    let debug = args.iter().any(|arg| arg == "--debug");
+    // `--minify` builds a script as small as it gets
+    let minify = args.iter().any(|arg| arg == "--minify");
+    args.retain(|arg| arg != "--debug" && arg != "--minify");
 
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        error!("no input file provided");
-        return;
+    match args.get(1).map(String::as_str) {
+        None => error!("no input file provided"),
+        Some("--source-line") => source_line(&args[2..]),
+        Some(file_path) => transpile(file_path, args.get(2), debug, minify),
     }
+}
 
-    let file_path = &args[1];
+/// Transpiles a script to stdout, or to a file with its source map written next to it
+fn transpile(file_path: &str, output: Option<&String>, debug: bool, minify: bool) {
     let absolute_path = match std::fs::canonicalize(file_path) {
         Ok(path) => path,
         Err(err) => {
@@ -29,8 +38,83 @@ fn main() {
         }
     };
 
-    match engine.exec_file(&absolute_path) {
+    let target = TranspilerTarget::default();
+    let options = TranspilerOptions {
+        source_map: output.is_some(),
+        debug,
+        minify,
+        ..target.options()
+    };
+
+    let mut engine = Engine::create_with(target, options);
+
+    let Some(output) = output else {
+        // logs go to stderr, so the transpiled program can be piped on its own
+        match engine.exec_file(&absolute_path, &mut std::io::stdout().lock()) {
+            Ok(status) => info!("finished with status {status}"),
+            Err(err) => error!("{err}")
+        };
+
+        return;
+    };
+
+    let mut script = Vec::new();
+    match engine.exec_file(&absolute_path, &mut script) {
         Ok(status) => info!("finished with status {status}"),
-        Err(err) => error!("{err}")
+        Err(err) => {
+            error!("{err}");
+            return;
+        }
     };
+
+    // the script is written next to the output and moved over it once it's complete,
+    // so a failed write leaves the previous output as it was
+    let temp_path = format!("{output}.tmp");
+    if let Err(err) = std::fs::write(&temp_path, &script).and_then(|_| std::fs::rename(&temp_path, output)) {
+        error!("Could not write {output}: {err}");
+        let _ = std::fs::remove_file(&temp_path);
+        return;
+    }
+
+    let Some(source_map) = engine.source_map() else {
+        return;
+    };
+
+    let map_path = format!("{output}.map");
+    if let Err(err) = File::create(&map_path).and_then(|mut file| source_map.write_to(&mut file)) {
+        error!("Could not write the source map to {map_path}: {err}");
+    }
+}
+
+/// `--source-line <script>.map <line>` prints the position in the source a line of a transpiled script comes from,
+/// e.g. for the line bash names when the script fails
+fn source_line(args: &[String]) {
+    let [map_path, line] = args else {
+        error!("usage: --source-line <script>.map <line>");
+        return;
+    };
+
+    let Ok(line) = line.parse::<usize>() else {
+        error!("'{line}' is not a line number");
+        return;
+    };
+
+    let source_map = match std::fs::read_to_string(map_path) {
+        Ok(text) => match SourceMap::parse(&text) {
+            Ok(source_map) => source_map,
+            Err(err) => {
+                error!("Could not read {map_path}: {err}");
+                return;
+            }
+        },
+        Err(err) => {
+            error!("Could not read {map_path}: {err}");
+            return;
+        }
+    };
+
+    match source_map.lookup(line) {
+        Some(span) => println!("{}:{}:{}", source_map.source(), span.start.line(), span.start.col()),
+        None => error!("line {line} of the script doesn't come from {}", source_map.source()),
+    }
 }
@@ -13,7 +13,7 @@ where E: Display {
 
     fn print_errors(&self) {
         for error in self.fetch_errors() {
-            println!("{}", error)
+            eprintln!("{}", error)
         }
     }
 
@@ -87,4 +87,9 @@ impl<T> WithCursor<T> {
             value,
         }
     }
+
+    /// The part of the source the value was read from
+    pub fn span(&self) -> WithCursor<()> {
+        WithCursor::create_with(self.start, self.end, ())
+    }
 }
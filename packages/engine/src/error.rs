@@ -8,6 +8,8 @@ pub enum EngineErrorKind {
     LexerError(#[from] crate::lexer::LexerError),
     #[error("{0}")]
     ParserError(#[from] crate::parser::ParserError),
+    #[error("{0}")]
+    TranspilerError(#[from] crate::transpiler::error::TranspilerError),
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error("exec error")]
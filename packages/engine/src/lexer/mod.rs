@@ -1,7 +1,7 @@
 use std::{iter::Peekable, str::Chars};
 
 use error::LexerResult;
-use tokens::{LexerLiteral, LexerToken, LexerTokenKind, LexerTokenList};
+use tokens::{LexerComment, LexerLiteral, LexerShellWord, LexerShellWordPart, LexerStringPart, LexerToken, LexerTokenKind, LexerTokenList, ShellCommand};
 
 use crate::{
     component::{ComponentErrors, ComponentIter}, cursor::Cursor, error::SourceFile
@@ -17,6 +17,8 @@ pub struct Lexer<'a> {
     errors: Vec<LexerError>,
     cursor: Cursor,
     tokens: LexerTokenList,
+    /// Comments waiting for the token after them
+    trivia: Vec<LexerComment>,
     max_int_len: u8,
     source_file: &'a SourceFile,
 }
@@ -41,6 +43,7 @@ impl<'a> Lexer<'a> {
             errors: Vec::new(),
             cursor: Cursor::create(),
             tokens: LexerTokenList::new(),
+            trivia: Vec::new(),
             max_int_len,
             source_file,
         };
@@ -58,7 +61,7 @@ impl<'a> Lexer<'a> {
             let start = self.cursor;
 
             if let Some(char) = self.next() {
-                match self.scan_char(&char) {
+                match self.scan_char(&char, start) {
                     Ok(Some((token_type, value))) => self.add_token(token_type, value, start),
                     Err(err) => self.add_error(start, err),
                     _ => {}
@@ -74,6 +77,7 @@ impl<'a> Lexer<'a> {
     fn scan_char(
         &mut self,
         char: &char,
+        start: Cursor,
     ) -> LexerResult<Option<(LexerTokenKind, Option<Box<LexerLiteral>>)>> {
         macro_rules! check_double {
             ($single_type:expr, $double:tt, $double_type:expr) => {
@@ -131,11 +135,13 @@ impl<'a> Lexer<'a> {
 
                 '/' => match self.peek() {
                     Some(&'/') => {
-                        self.consume_single_line_comment()?;
+                        let comment = self.consume_single_line_comment(start)?;
+                        self.trivia.push(comment);
                         return Ok(None);
                     }
                     Some(&'*') => {
-                        self.consume_multi_line_comment()?;
+                        let comment = self.consume_multi_line_comment(start)?;
+                        self.trivia.push(comment);
                         return Ok(None);
                     }
                     Some(&'=') => {
@@ -218,11 +224,18 @@ impl<'a> Lexer<'a> {
         value: Option<Box<LexerLiteral>>,
         start: Cursor,
     ) {
+        // comments are kept for the statement after them, not for the end of their line
+        let trivia = match token_type {
+            LexerTokenKind::EOL => Vec::new(),
+            _ => std::mem::take(&mut self.trivia),
+        };
+
         self.tokens.push(LexerToken {
             kind: token_type,
             start,
             end: self.cursor,
             value,
+            trivia,
         });
     }
 
@@ -239,28 +252,52 @@ impl<'a> Lexer<'a> {
         })
     }
 
-    /// Consumes a single-line comment (aka skips to the end of the line and returns nothing)
-    fn consume_single_line_comment(&mut self) -> LexerResult<()> {
-        self.eat_until(&['\n'], false);
-        self.next();
+    /// Consumes a single-line comment up to the end of the line, which is left to end the statement before it
+    fn consume_single_line_comment(&mut self, start: Cursor) -> LexerResult<LexerComment> {
+        self.expect_char(&'/')?;
+        let text = self.eat_until(&['\n'], false).unwrap_or_default();
+
+        // `///` documents the function after it, while a line of slashes is a plain comment
+        let (text, doc) = match text.strip_prefix('/') {
+            Some(doc) if !doc.starts_with('/') => (doc.to_string(), true),
+            _ => (text, false),
+        };
 
-        Ok(())
+        Ok(LexerComment {
+            text,
+            doc,
+            start,
+            end: self.cursor,
+        })
     }
 
     /// Consumes a multi-line comment (skips until it reaches */)
-    fn consume_multi_line_comment(&mut self) -> LexerResult<()> {
-        self.skip_until(&['*']);
+    fn consume_multi_line_comment(&mut self, start: Cursor) -> LexerResult<LexerComment> {
         self.expect_char(&'*')?;
-        if self.expect(&'/').is_err() {
-            return self.consume_multi_line_comment();
+        let mut text = String::new();
+
+        loop {
+            text.push_str(&self.eat_until(&['*'], false).unwrap_or_default());
+            self.expect_char(&'*')?;
+
+            if self.next_if_eq(&'/').is_some() {
+                break;
+            }
+
+            text.push('*');
         }
 
-        Ok(())
+        Ok(LexerComment {
+            text,
+            doc: false,
+            start,
+            end: self.cursor,
+        })
     }
 
-    /// Attempts to return a [`TokenType::String`]
+    /// Attempts to return a [`TokenType::String`], or a [`TokenType::InterpolatedString`] if it embeds expressions
     fn consume_string(&mut self) -> LexerResult<(LexerTokenKind, Option<Box<LexerLiteral>>)> {
-        let string = self.eat_until(&['"', '\n'], true).unwrap_or_default();
+        let mut parts = self.eat_string_parts(&['"', '\n'])?;
         
         if let Err(err) = self.expect_char(&'"') {
             if let LexerErrorKind::ExpectedCharacter { found: Some(found), .. } = &err {
@@ -269,44 +306,279 @@ impl<'a> Lexer<'a> {
             
             return Err(err);
         }
-    
-        Ok((
-            LexerTokenKind::String,
-            Some(Box::from(LexerLiteral::String(Box::from(string)))),
-        ))
+
+        Ok(match parts.as_mut_slice() {
+            [] => (
+                LexerTokenKind::String,
+                Some(Box::from(LexerLiteral::String(Box::default()))),
+            ),
+            [LexerStringPart::Literal(string)] => (
+                LexerTokenKind::String,
+                Some(Box::from(LexerLiteral::String(Box::from(std::mem::take(string))))),
+            ),
+            _ => (
+                LexerTokenKind::InterpolatedString,
+                Some(Box::from(LexerLiteral::InterpolatedString(Box::from(parts)))),
+            ),
+        })
     }
 
     /// Attempts to return a [`TokenType::ShellCommand`]
     fn consume_shell_command(
         &mut self,
     ) -> LexerResult<(LexerTokenKind, Option<Box<LexerLiteral>>)> {
-        let cmd_name = self
+        let name = self
             .eat_until(&[' ', '\t', '\n', '('], false)
             .ok_or(LexerErrorKind::UnexpectedEnd)?;
 
-        let cmd_args = match self.peek() {
-            Some(' ' | '\t') => {
-                self.next();
-                self.eat_until(&['\n'], false)
-            }
+        let args = match self.peek() {
+            Some(' ' | '\t') => self.eat_shell_words(false)?,
             Some('(') => {
                 self.next();
-                if let Some(res) = self.eat_until(&['\n', '\0', ')'], true) {
-                    self.expect_char(&')')?;
-                    Some(res)
-                } else {
-                    None
-                }
+                let words = self.eat_shell_words(true)?;
+                self.expect_char(&')')?;
+                words
             }
-            _ => None,
+            _ => Vec::new(),
         };
 
         Ok((
             LexerTokenKind::ShellCommand,
-            Some(Box::from(LexerLiteral::ShellCommand(Box::from((cmd_name, cmd_args))))),
+            Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand { name, args })))),
         ))
     }
 
+    /// Splits the arguments of a shell command into words, up until the end of the line,
+    /// or the closing `)` if the arguments are `enclosed` in parentheses
+    fn eat_shell_words(&mut self, enclosed: bool) -> LexerResult<Vec<LexerShellWord>> {
+        fn push_literal(word: &mut LexerShellWord, text: &str) {
+            match word.last_mut() {
+                Some(LexerShellWordPart::Literal(previous)) => previous.push_str(text),
+                _ => word.push(LexerShellWordPart::Literal(text.to_string())),
+            }
+        }
+
+        let mut words = Vec::new();
+        let mut word = LexerShellWord::new();
+        let mut depth: usize = 0;
+
+        while let Some(char) = self.peek().copied() {
+            let following = self.chars.clone().nth(1);
+
+            match char {
+                '\n' => break,
+                ')' if enclosed && depth == 0 => break,
+
+                ' ' | '\t' => {
+                    self.next();
+
+                    if !word.is_empty() {
+                        words.push(std::mem::take(&mut word));
+                    }
+                }
+
+                '#' if following == Some('*') && self.chars.clone().nth(2) == Some('{') => {
+                    self.next();
+                    self.next();
+                    self.next();
+                    word.push(LexerShellWordPart::UnquotedInterpolation(self.consume_interpolation()?));
+                }
+
+                // comments are only recognised at the start of a word
+                '#' | '/' if !enclosed && word.is_empty() && following != Some('{') && (char == '#' || following == Some('/')) => {
+                    self.eat_until(&['\n'], false);
+                    break;
+                }
+
+                '#' if following == Some('{') => {
+                    self.next();
+                    self.next();
+                    word.push(LexerShellWordPart::Interpolation(self.consume_interpolation()?));
+                }
+
+                '\'' => {
+                    self.next();
+                    let text = self.eat_until(&['\'', '\n'], false).unwrap_or_default();
+                    self.expect_char(&'\'')?;
+                    word.push(LexerShellWordPart::SingleQuoted(text));
+                }
+
+                '"' => {
+                    self.next();
+                    word.push(LexerShellWordPart::DoubleQuoted(self.eat_shell_double_quoted()?));
+                }
+
+                '\\' => {
+                    self.next();
+
+                    match self.next() {
+                        // line continuation
+                        Some('\n') => {}
+                        Some(escaped) => push_literal(&mut word, &format!("\\{escaped}")),
+                        None => return Err(LexerErrorKind::UnexpectedEnd),
+                    }
+                }
+
+                _ => {
+                    // parentheses are only counted to find the end of enclosed arguments
+                    match char {
+                        '(' if enclosed => depth += 1,
+                        ')' if enclosed => depth -= 1,
+                        _ => {}
+                    }
+
+                    self.next();
+                    push_literal(&mut word, &char.to_string());
+                }
+            }
+        }
+
+        if !word.is_empty() {
+            words.push(word);
+        }
+
+        Ok(words)
+    }
+
+    /// Consumes a double quoted shell string, keeping escape sequences as they were written
+    /// for the shell to handle. An interpolation can be escaped with `\#{`
+    fn eat_shell_double_quoted(&mut self) -> LexerResult<Vec<LexerStringPart>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            let Some(char) = self.peek().copied() else {
+                return Err(LexerErrorKind::UnexpectedEnd);
+            };
+
+            match char {
+                '"' => {
+                    self.next();
+                    break;
+                }
+
+                '\n' => return Err(LexerErrorKind::ExpectedCharacter {
+                    expected: String::from("\""),
+                    found: Some('\n'),
+                }),
+
+                '\\' => {
+                    self.next();
+
+                    match self.next() {
+                        Some('#') => text.push('#'),
+                        Some(escaped) => {
+                            text.push('\\');
+                            text.push(escaped);
+                        }
+                        None => return Err(LexerErrorKind::UnexpectedEnd),
+                    }
+                }
+
+                '#' if self.chars.clone().nth(1) == Some('{') => {
+                    self.next();
+                    self.next();
+
+                    if !text.is_empty() {
+                        parts.push(LexerStringPart::Literal(std::mem::take(&mut text)));
+                    }
+
+                    parts.push(LexerStringPart::Expression(self.consume_interpolation()?));
+                }
+
+                _ => {
+                    self.next();
+                    text.push(char);
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(LexerStringPart::Literal(text));
+        }
+
+        Ok(parts)
+    }
+
+    /// Iterates until it reaches the closing character, splitting the text on `#{...}` interpolations.
+    /// An interpolation can be escaped with `\#{`
+    fn eat_string_parts(&mut self, term: &[char]) -> LexerResult<Vec<LexerStringPart>> {
+        let mut parts = Vec::new();
+
+        loop {
+            let literal = self.eat_until_conditional(|c| c == &'#' || term.contains(c), true);
+
+            if let Some(literal) = literal {
+                match parts.last_mut() {
+                    Some(LexerStringPart::Literal(previous)) => previous.push_str(&literal),
+                    _ => parts.push(LexerStringPart::Literal(literal)),
+                }
+            }
+
+            if self.next_if_eq(&'#').is_none() {
+                break;
+            }
+
+            if self.next_if_eq(&'{').is_some() {
+                parts.push(LexerStringPart::Expression(self.consume_interpolation()?));
+                continue;
+            }
+
+            match parts.last_mut() {
+                Some(LexerStringPart::Literal(previous)) => previous.push('#'),
+                _ => parts.push(LexerStringPart::Literal(String::from("#"))),
+            }
+        }
+
+        Ok(parts)
+    }
+
+    /// Lexes the tokens of an embedded expression up until the closing `}`
+    fn consume_interpolation(&mut self) -> LexerResult<LexerTokenList> {
+        // the comments before the command or string are kept for it, not for the embedded tokens
+        let trivia = std::mem::take(&mut self.trivia);
+        let outer = std::mem::take(&mut self.tokens);
+        let result = self.scan_interpolation();
+        let tokens = std::mem::replace(&mut self.tokens, outer);
+        self.trivia = trivia;
+
+        result.map(|_| tokens)
+    }
+
+    fn scan_interpolation(&mut self) -> LexerResult<()> {
+        let mut depth: usize = 0;
+
+        loop {
+            let start = self.cursor;
+
+            match self.peek() {
+                None => return Err(LexerErrorKind::UnexpectedEnd),
+                Some('\n') => return Err(LexerErrorKind::ExpectedCharacter {
+                    expected: String::from("}"),
+                    found: Some('\n'),
+                }),
+                Some('}') if depth == 0 => {
+                    self.add_token(LexerTokenKind::EOF, None, start);
+                    self.next();
+                    return Ok(());
+                },
+                _ => {}
+            }
+
+            if let Some(char) = self.next() {
+                if let Some((token_type, value)) = self.scan_char(&char, start)? {
+                    match token_type {
+                        LexerTokenKind::LBracket => depth += 1,
+                        LexerTokenKind::RBracket => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+
+                    self.add_token(token_type, value, start);
+                }
+            }
+        }
+    }
+
     /// Returns true if the char is a valid character for an identifier, false otherwies
     fn is_valid_identifier(char: &char) -> bool {
         match char {
@@ -109,6 +109,8 @@ pub enum LexerTokenKind {
 
     Identifier,
     String,
+    /// `"... #{expr} ..."`
+    InterpolatedString,
     Integer,
     Boolean,
     ShellCommand,
@@ -125,6 +127,8 @@ pub enum LexerLiteral {
     Identifier(Box<String>),
     /// `"..."`
     String(Box<String>),
+    /// `"Hello #{name}!"`
+    InterpolatedString(Box<Vec<LexerStringPart>>),
     /// e.g. `1` OR `-5`
     Integer(isize),
     /// `true` OR `false`
@@ -148,6 +152,13 @@ impl LexerLiteral {
         }
     }
 
+    pub fn as_interpolated_string(&self) -> Option<&Vec<LexerStringPart>> {
+        match self {
+            Self::InterpolatedString(parts) => Some(parts),
+            _ => None,
+        }
+    }
+
     pub fn as_integer(&self) -> Option<&isize> {
         match self {
             Self::Integer(int) => Some(int),
@@ -170,14 +181,59 @@ impl LexerLiteral {
     }
 }
 
-pub type ShellCommand = (String, Option<String>);
+/// A piece of text which may embed expressions with `#{...}`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LexerStringPart {
+    /// Text which is kept as is
+    Literal(String),
+    /// The tokens of an embedded `#{expr}`, terminated by an EOF token
+    Expression(LexerTokenList),
+}
+
+/// `$cmd arg1 arg2` OR `$cmd(arg1 arg2)`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ShellCommand {
+    pub name: String,
+    pub args: Vec<LexerShellWord>,
+}
+
+/// A single argument of a shell command, made of the adjacent parts it was written as,
+/// e.g. `--name="#{name}"` is a literal followed by a double quoted string
+pub type LexerShellWord = Vec<LexerShellWordPart>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LexerShellWordPart {
+    /// Unquoted text as it was written, keeping its meaning to the shell (globs, pipes, escapes...)
+    Literal(String),
+    /// `'...'`, which is never interpolated
+    SingleQuoted(String),
+    /// `"..."`, the literal parts are kept as they were written
+    DoubleQuoted(Vec<LexerStringPart>),
+    /// `#{expr}` outside of quotes
+    Interpolation(LexerTokenList),
+    /// `#*{expr}`, expanded without quotes so the value is split into words
+    UnquotedInterpolation(LexerTokenList),
+}
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct LexerToken {
     pub kind: LexerTokenKind,
     pub start: Cursor,
     pub end: Cursor,
     pub value: Option<Box<LexerLiteral>>,
+    /// The comments written in front of the token
+    pub trivia: Vec<LexerComment>,
+}
+
+/// `// ...`, `/// ...` OR `/* ... */`, which isn't a token itself but trivia of the token after it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LexerComment {
+    /// The text after the slashes, or between `/*` and `*/`
+    pub text: String,
+    /// `///`, documenting the function after it
+    pub doc: bool,
+    pub start: Cursor,
+    pub end: Cursor,
 }
 
 impl LexerToken {
@@ -201,6 +257,16 @@ impl LexerToken {
             ))
     }
 
+    pub fn as_interpolated_string(&self) -> LexerResult<&Vec<LexerStringPart>> {
+        self.value
+            .as_ref()
+            .and_then(|v| v.as_interpolated_string())
+            .ok_or(LexerErrorKind::LiteralExtractionError(
+                LexerTokenKind::InterpolatedString,
+                self.kind.clone(),
+            ))
+    }
+
     pub fn as_integer(&self) -> LexerResult<&isize> {
         self.value
             .as_ref()
@@ -1,18 +1,18 @@
-#![allow(incomplete_features)]
+#![allow(incomplete_features, unused_features)]
 #![feature(try_blocks)]
 #![feature(guard_patterns)]
 
 #[macro_use]
 extern crate log;
 
-use std::path::PathBuf;
+use std::{io::Write, path::PathBuf};
 
 use component::ComponentErrors;
 use error::{EngineResult, SourceFile};
 
 pub use lexer::Lexer;
 pub use parser::Parser;
-use transpiler::{Transpiler, TranspilerTarget};
+use transpiler::{source_map::SourceMap, Target, Transpiler, TranspilerOptions, TranspilerTarget};
 
 pub mod component;
 pub mod constants;
@@ -26,16 +26,35 @@ pub mod transpiler;
 #[derive(Default)]
 pub struct Engine {
     target: TranspilerTarget,
+    /// Falls back to the options of the target
+    options: Option<TranspilerOptions>,
+    /// The source map of the last script, if the options ask for one
+    source_map: Option<SourceMap>,
 }
 
 impl Engine {
     pub fn create(target: TranspilerTarget) -> Self {
         Self {
-            target
+            target,
+            options: None,
+            source_map: None,
         }
     }
 
-    pub fn exec_file(&mut self, file: &PathBuf) -> EngineResult<i32> {
+    pub fn create_with(target: TranspilerTarget, options: TranspilerOptions) -> Self {
+        Self {
+            target,
+            options: Some(options),
+            source_map: None,
+        }
+    }
+
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_ref()
+    }
+
+    /// Transpiles a file, writing the program in the target language to `out` unless it fails
+    pub fn exec_file(&mut self, file: &PathBuf, out: &mut impl Write) -> EngineResult<i32> {
         debug!("attempting to read file {file:?}");
 
         if !file.is_file() {
@@ -50,14 +69,14 @@ impl Engine {
             .to_string_lossy()
             .to_string();
 
-        self.exec_source_file(SourceFile::from(code, Some(path)))
+        self.exec_source_file(SourceFile::from(code, Some(path)), out)
     }
 
-    pub fn exec(&mut self, code: &str) -> EngineResult<i32> {
-        self.exec_source_file(SourceFile::from(code.to_string(), None))
+    pub fn exec(&mut self, code: &str, out: &mut impl Write) -> EngineResult<i32> {
+        self.exec_source_file(SourceFile::from(code.to_string(), None), out)
     }
 
-    pub fn exec_source_file(&mut self, source_file: error::SourceFile) -> EngineResult<i32> {
+    pub fn exec_source_file(&mut self, source_file: error::SourceFile, out: &mut impl Write) -> EngineResult<i32> {
         let mut lexer = Lexer::create(&source_file);
 
         lexer.tokens();
@@ -73,9 +92,21 @@ impl Engine {
             return Err(error::EngineErrorKind::ExecError);
         }
 
-        let mut transpiler = Transpiler::create(&self.target, parser.parse());
-        println!("---START---\n{}\n---END---", transpiler.transpile());
+        let options = self.options.clone().unwrap_or_else(|| self.target.options());
+        let record_map = options.source_map;
+        let mut transpiler = Transpiler::create_with(&self.target, options, parser.parse(), &source_file);
+
+        // the script is only written out once it's complete, so a failed one doesn't leave part of it behind
+        let mut code = Vec::new();
+        transpiler.transpile_to(&mut code)?;
+        self.source_map = record_map.then(|| transpiler.source_map().clone());
+        if transpiler.has_errors() {
+            transpiler.print_errors();
+            return Err(error::EngineErrorKind::ExecError);
+        }
 
+        out.write_all(&code)?;
+        out.flush()?;
         Ok(0)
     }
 }
@@ -1,6 +1,6 @@
-use std::{collections::HashMap, fmt::Debug, rc::Rc};
+use std::fmt::Debug;
 
-use crate::{cursor::WithCursor, lexer::tokens::{LexerToken, LexerTokenKind, ShellCommand}};
+use crate::{cursor::WithCursor, lexer::tokens::{LexerToken, LexerTokenKind}};
 
 use super::ParserErrorKind;
 
@@ -14,6 +14,7 @@ pub enum Expression {
     Assignment(Box<(WithCursor<Expression>, WithCursor<AssignmentOperator>, WithCursor<Expression>)>),
     Range(Box<(WithCursor<Expression>, WithCursor<Expression>, bool)>),
     ShellCommand(Box<ShellCommand>),
+    InterpolatedString(Box<InterpolatedString>),
     Identifier(Box<Identifier>),
     FunctionCall(Box<(Identifier, Vec<WithCursor<Expression>>)>),
     If(Box<(WithCursor<Expression>, WithCursor<Block>, Option<Else>)>),
@@ -22,23 +23,92 @@ pub enum Expression {
 }
 
 pub type Else = WithCursor<Block>;
-pub type MatchCase = HashMap<WithCursor<Literal>, Rc<WithCursor<Expression>>>;
+pub type InterpolatedString = Vec<StringPart>;
+pub type MatchCase = Vec<MatchArm>;
+
+/// `pattern || pattern => value`, the arms are kept in the order they were written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchArm {
+    pub patterns: Vec<WithCursor<MatchPattern>>,
+    pub value: WithCursor<Expression>,
+}
+
+#[derive(lang_macro::EnumVariants, Debug, Clone, PartialEq, Eq)]
+pub enum MatchPattern {
+    Literal(Literal),
+    /// `*`, matches any value
+    Wildcard,
+}
+
+
+#[derive(lang_macro::EnumVariants, Debug, Clone, PartialEq, Eq)]
+pub enum StringPart {
+    Literal(String),
+    Expression(WithCursor<Expression>),
+}
+
+
+/// `$cmd arg1 arg2` OR `$cmd(arg1 arg2)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellCommand {
+    pub name: String,
+    pub args: Vec<ShellWord>,
+}
+
+/// A single argument of a shell command, made of the adjacent parts it was written as
+pub type ShellWord = Vec<ShellWordPart>;
+
+#[derive(lang_macro::EnumVariants, Debug, Clone, PartialEq, Eq)]
+pub enum ShellWordPart {
+    /// Unquoted text as it was written
+    Literal(String),
+    /// `'...'`
+    SingleQuoted(String),
+    /// `"..."`, the literal parts are kept as they were written
+    DoubleQuoted(InterpolatedString),
+    /// `#{expr}` outside of quotes
+    Interpolation(WithCursor<Expression>),
+    /// `#*{expr}`, expanded without quotes so the value is split into words
+    UnquotedInterpolation(WithCursor<Expression>),
+}
 
 
 #[derive(lang_macro::EnumVariants, Debug, Clone, PartialEq, Eq)]
 pub enum Statement {
-    While(Box<(WithCursor<Expression>, WithCursor<Block>)>),
-    For(Box<(Variable, WithCursor<Expression>, WithCursor<Block>)>),
-    Return(Box<Option<WithCursor<Expression>>>),
+    While(Box<WithCursor<WhileLoop>>),
+    For(Box<WithCursor<ForLoop>>),
+    Return(Box<WithCursor<Option<WithCursor<Expression>>>>),
     If(Box<WithCursor<Expression>>),
     Match(Box<WithCursor<Expression>>),
     Expression(Box<WithCursor<Expression>>),
-    Continue,
-    Break,
-    Variable(Box<Variable>),
-    Constant(Box<Variable>),
-    Function(Box<Function>),
-    Include(Box<String>),
+    /// The expression a block ends with, which is the value of the block
+    Tail(Box<WithCursor<Expression>>),
+    /// The keyword, so misplaced ones can be pointed at
+    Continue(WithCursor<()>),
+    Break(WithCursor<()>),
+    Variable(Box<WithCursor<Variable>>),
+    Constant(Box<WithCursor<Variable>>),
+    Function(Box<WithCursor<Function>>),
+    Include(Box<WithCursor<String>>),
+    /// A comment written in front of the statement after it, without the `//` or `/* */`
+    Comment(Box<WithCursor<String>>),
+}
+
+impl Statement {
+    /// The part of the source a statement was parsed from, starting at its keyword
+    pub fn span(&self) -> WithCursor<()> {
+        match self {
+            Statement::While(while_loop) => while_loop.span(),
+            Statement::For(for_loop) => for_loop.span(),
+            Statement::Return(value) => value.span(),
+            Statement::If(expr) | Statement::Match(expr) | Statement::Expression(expr) | Statement::Tail(expr) => expr.span(),
+            Statement::Continue(keyword) | Statement::Break(keyword) => keyword.span(),
+            Statement::Variable(var) | Statement::Constant(var) => var.span(),
+            Statement::Function(func) => func.span(),
+            Statement::Include(path) => path.span(),
+            Statement::Comment(comment) => comment.span(),
+        }
+    }
 }
 
 
@@ -46,6 +116,16 @@ pub enum Statement {
 pub type ProgramTree = Block;
 pub type Identifier = String;
 pub type Block = Vec<Statement>;
+/// The condition and the body
+pub type WhileLoop = (WithCursor<Expression>, WithCursor<Block>);
+/// The variable, the value it iterates over and the body
+pub type ForLoop = (Variable, WithCursor<Expression>, WithCursor<Block>);
+
+/// Splits a block before the comments it ends with, so the statements come first and end with its tail
+pub fn split_trailing_comments(block: &[Statement]) -> (&[Statement], &[Statement]) {
+    let end = block.iter().rposition(|statement| !matches!(statement, Statement::Comment(_))).map_or(0, |index| index + 1);
+    block.split_at(end)
+}
 
 #[derive(lang_macro::EnumVariants, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Literal {
@@ -173,6 +253,8 @@ impl TryFrom<LexerTokenKind> for AssignmentOperator {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Function {
     pub name: String,
+    /// The lines of the `///` comments in front of the function
+    pub docs: Vec<String>,
     pub parameters: Option<Vec<Variable>>,
     pub strict_type: Option<String>,
     pub body: WithCursor<Block>,
@@ -1,8 +1,9 @@
-use std::{iter::Peekable, rc::Rc, slice::Iter};
+use std::{iter::Peekable, slice::Iter};
 
 use ast::{
     ArithmeticOperator, AssignmentOperator, Block, Expression, Function, Identifier, Literal,
-    LogicalOperator, MatchCase, ProgramTree, Statement, UnaryOperator, Variable,
+    InterpolatedString, LogicalOperator, MatchArm, MatchCase, MatchPattern, ProgramTree, ShellCommand, ShellWordPart,
+    Statement, StringPart, UnaryOperator, Variable,
 };
 use error::ParserResult;
 
@@ -10,7 +11,7 @@ use crate::{
     component::{ComponentErrors, ComponentIter},
     cursor::{Cursor, WithCursor},
     error::SourceFile,
-    lexer::tokens::{LexerToken, LexerTokenKind, LexerTokenList},
+    lexer::tokens::{LexerShellWordPart, LexerStringPart, LexerToken, LexerTokenKind, LexerTokenList},
 };
 
 pub use error::{ParserError, ParserErrorKind};
@@ -67,6 +68,8 @@ impl<'a> Parser<'a> {
         }
 
         while let Some(token) = self.peek().cloned() {
+            self.tree.extend(leading_comments(token));
+
             match self.parse_statement(token) {
                 Ok(Some(statement)) => self.tree.push(statement),
                 Err(err) => {
@@ -97,14 +100,16 @@ impl<'a> Parser<'a> {
         use LexerTokenKind::*;
         Ok(match &token.kind {
             Var => self.stmt_var()?,
+            Const => self.stmt_const()?,
+            Include => self.stmt_include()?,
             Function => self.stmt_func()?,
             If => self.stmt_if()?,
             Match => self.stmt_match()?,
             For => self.stmt_for()?,
             While => self.stmt_while()?,
             Return => self.stmt_return()?,
-            Break => Some(Statement::Break),
-            Continue => Some(Statement::Continue),
+            Break => Some(Statement::Break(WithCursor::create_with(token.start, token.end, ()))),
+            Continue => Some(Statement::Continue(WithCursor::create_with(token.start, token.end, ()))),
             EOF | EOL => return Ok(None),
 
             _ => {
@@ -116,11 +121,35 @@ impl<'a> Parser<'a> {
 
     // MARK: Variable
     fn stmt_var(&mut self) -> ParserResult<Option<Statement>> {
+        let start = self.expect_token(&LexerTokenKind::Var)?.start;
+        let value = self.parse_var()?;
+        self.peek_terminator()?;
+
+        Ok(Some(Statement::Variable(Box::from(WithCursor::create_with(start, self.cursor, value)))))
+    }
+
+    // MARK: Constant
+    fn stmt_const(&mut self) -> ParserResult<Option<Statement>> {
+        let start = self.expect_token(&LexerTokenKind::Const)?.start;
         self.expect_token(&LexerTokenKind::Var)?;
         let value = self.parse_var()?;
-        self.expect_terminator()?;
+        self.peek_terminator()?;
 
-        Ok(Some(Statement::Variable(Box::from(value))))
+        Ok(Some(Statement::Constant(Box::from(WithCursor::create_with(start, self.cursor, value)))))
+    }
+
+    // MARK: Include
+    fn stmt_include(&mut self) -> ParserResult<Option<Statement>> {
+        let start = self.expect_token(&LexerTokenKind::Include)?.start;
+
+        let path = self
+            .expect_token(&LexerTokenKind::String)?
+            .as_string()?
+            .clone();
+
+        self.peek_terminator()?;
+
+        Ok(Some(Statement::Include(Box::from(WithCursor::create_with(start, self.cursor, path)))))
     }
 
     fn parse_var(&mut self) -> ParserResult<Variable> {
@@ -161,7 +190,14 @@ impl<'a> Parser<'a> {
 
     // MARK: Function
     fn stmt_func(&mut self) -> ParserResult<Option<Statement>> {
-        self.expect_token(&LexerTokenKind::Function)?;
+        let keyword = self.expect_token(&LexerTokenKind::Function)?;
+        let start = keyword.start;
+        let docs = keyword
+            .trivia
+            .iter()
+            .filter(|comment| comment.doc)
+            .map(|comment| comment.text.clone())
+            .collect();
 
         let identifier = self
             .expect_token(&LexerTokenKind::Identifier)?
@@ -200,17 +236,18 @@ impl<'a> Parser<'a> {
 
         let function = Function {
             name: identifier.to_owned(),
+            docs,
             parameters,
             strict_type,
             body,
         };
 
-        Ok(Some(Statement::Function(Box::from(function))))
+        Ok(Some(Statement::Function(Box::from(WithCursor::create_with(start, self.cursor, function)))))
     }
 
     // MARK: For
     fn stmt_for(&mut self) -> ParserResult<Option<Statement>> {
-        self.expect_token(&LexerTokenKind::For)?;
+        let start = self.expect_token(&LexerTokenKind::For)?.start;
 
         let identifier = self
             .expect_token(&LexerTokenKind::Identifier)?
@@ -227,28 +264,32 @@ impl<'a> Parser<'a> {
 
         let block = self.stmt_block()?;
 
-        Ok(Some(Statement::For(Box::from((variable, expr, block)))))
+        Ok(Some(Statement::For(Box::from(WithCursor::create_with(start, self.cursor, (variable, expr, block))))))
     }
 
     // MARK: While
     fn stmt_while(&mut self) -> ParserResult<Option<Statement>> {
-        self.expect_token(&LexerTokenKind::While)?;
+        let start = self.expect_token(&LexerTokenKind::While)?.start;
 
         let_expr!(condition = self.expression()?);
         self.next();
 
         let block = self.stmt_block()?;
 
-        Ok(Some(Statement::While(Box::from((condition, block)))))
+        Ok(Some(Statement::While(Box::from(WithCursor::create_with(start, self.cursor, (condition, block))))))
     }
 
     // MARK: Return
     fn stmt_return(&mut self) -> ParserResult<Option<Statement>> {
-        self.expect_token(&LexerTokenKind::Return)?;
+        let start = self.expect_token(&LexerTokenKind::Return)?.start;
 
-        let value = self.expression()?;
+        let value = if self.peek_terminator().is_ok() {
+            None
+        } else {
+            self.expression()?
+        };
 
-        Ok(Some(Statement::Return(Box::from(value))))
+        Ok(Some(Statement::Return(Box::from(WithCursor::create_with(start, self.cursor, value)))))
     }
 
     fn expression(&mut self) -> ParserResult<Option<WithCursor<Expression>>> {
@@ -257,7 +298,7 @@ impl<'a> Parser<'a> {
 
     // MARK: Assignment
     fn expr_assignment(&mut self) -> ParserResult<Option<WithCursor<Expression>>> {
-        let_expr!(lhs = self.expr_logic_or()?);
+        let_expr!(lhs = self.expr_range()?);
 
         if let Some(op_token) = self.next_if_eq_mul(&[
             &LexerTokenKind::Equal,
@@ -266,7 +307,7 @@ impl<'a> Parser<'a> {
             &LexerTokenKind::MultiplyEqual,
             &LexerTokenKind::DivideEqual,
         ]) {
-            let_expr!(rhs = self.expr_logic_or()?);
+            let_expr!(rhs = self.expr_range()?);
 
             if let WithCursor {
                 value: Expression::Identifier(_),
@@ -290,6 +331,27 @@ impl<'a> Parser<'a> {
         Ok(Some(lhs))
     }
 
+    // MARK: Range
+    /// Ranges bind the loosest, so `a + 1..b * 2` is `(a + 1)..(b * 2)`
+    fn expr_range(&mut self) -> ParserResult<Option<WithCursor<Expression>>> {
+        let_expr!(lhs = self.expr_logic_or()?);
+
+        let inclusive = match self.peek().map(|t| &t.kind) {
+            Some(LexerTokenKind::Range) => false,
+            Some(LexerTokenKind::RangeInclusive) => true,
+            _ => return Ok(Some(lhs)),
+        };
+
+        self.next();
+        let_expr!(rhs = self.expr_logic_or()?);
+
+        Ok(Some(WithCursor::create_with(
+            lhs.start,
+            rhs.end,
+            Expression::Range(Box::from((lhs, rhs, inclusive))),
+        )))
+    }
+
     // MARK: Logic OR
     fn expr_logic_or(&mut self) -> ParserResult<Option<WithCursor<Expression>>> {
         let_expr!(mut lhs = self.expr_logic_and()?);
@@ -442,7 +504,9 @@ impl<'a> Parser<'a> {
     // MARK: Unary
     fn expr_unary(&mut self) -> ParserResult<Option<WithCursor<Expression>>> {
         let start = self.cursor;
-        if let Some(token_unary) = self.next_if_eq(&&LexerTokenKind::Not) {
+        if let Some(token_unary) =
+            self.next_if_eq_mul(&[&LexerTokenKind::Not, &LexerTokenKind::Minus])
+        {
             let_expr!(rhs = self.expr_unary()?);
             
             let operator: UnaryOperator = token_unary.kind.clone().try_into()?;
@@ -471,9 +535,7 @@ impl<'a> Parser<'a> {
                 let mut args = vec![];
                 let mut end = self.cursor;
 
-                while let Some(token) = self.peek() {
-                    dbg!(token);
-
+                while self.peek().is_some() {
                     if self.next_if_eq(&&LexerTokenKind::RParen).is_some() {
                         end = self.cursor;
                         break;
@@ -520,8 +582,23 @@ impl<'a> Parser<'a> {
                 Expression::Identifier(Box::from(Identifier::from(token.as_identifier()?)))
             }
 
+            LexerTokenKind::InterpolatedString => Expression::InterpolatedString(Box::from(
+                self.parse_interpolation(token.as_interpolated_string()?)?,
+            )),
+
             LexerTokenKind::ShellCommand => {
-                Expression::ShellCommand(Box::from(token.as_shell_command()?.to_owned()))
+                let command = token.as_shell_command()?;
+
+                let args = command
+                    .args
+                    .iter()
+                    .map(|word| word.iter().map(|part| self.parse_shell_word_part(part)).collect())
+                    .collect::<ParserResult<_>>()?;
+
+                Expression::ShellCommand(Box::from(ShellCommand {
+                    name: command.name.to_owned(),
+                    args,
+                }))
             }
 
             LexerTokenKind::LParen => {
@@ -545,31 +622,53 @@ impl<'a> Parser<'a> {
             _ => return Err(ParserErrorKind::UnexpectedToken(token.kind.clone())),
         };
 
-        let_expr!(post = self.expr_range(WithCursor::create_with(token.start, token.end, expr))?);
+        Ok(Some(WithCursor::create_with(token.start, token.end, expr)))
+    }
 
-        Ok(Some(post))
+    // MARK: Interpolation
+    fn parse_interpolation(&self, parts: &'a [LexerStringPart]) -> ParserResult<InterpolatedString> {
+        parts
+            .iter()
+            .map(|part| {
+                Ok(match part {
+                    LexerStringPart::Literal(text) => StringPart::Literal(text.to_owned()),
+                    LexerStringPart::Expression(tokens) => {
+                        StringPart::Expression(self.parse_embedded(tokens)?)
+                    }
+                })
+            })
+            .collect()
     }
 
-    // MARK: Range
-    fn expr_range(
-        &mut self,
-        lhs: WithCursor<Expression>,
-    ) -> ParserResult<Option<WithCursor<Expression>>> {
-        let_expr!(peek = self.peek());
-        let inclusive = match peek.kind {
-            LexerTokenKind::Range => false,
-            LexerTokenKind::RangeInclusive => true,
-            _ => return Ok(Some(lhs)),
+    fn parse_shell_word_part(&self, part: &'a LexerShellWordPart) -> ParserResult<ShellWordPart> {
+        Ok(match part {
+            LexerShellWordPart::Literal(text) => ShellWordPart::Literal(text.to_owned()),
+            LexerShellWordPart::SingleQuoted(text) => ShellWordPart::SingleQuoted(text.to_owned()),
+            LexerShellWordPart::DoubleQuoted(parts) => {
+                ShellWordPart::DoubleQuoted(self.parse_interpolation(parts)?)
+            }
+            LexerShellWordPart::Interpolation(tokens) => {
+                ShellWordPart::Interpolation(self.parse_embedded(tokens)?)
+            }
+            LexerShellWordPart::UnquotedInterpolation(tokens) => {
+                ShellWordPart::UnquotedInterpolation(self.parse_embedded(tokens)?)
+            }
+        })
+    }
+
+    /// Parses the tokens of a `#{...}` into a single expression
+    fn parse_embedded(&self, tokens: &'a LexerTokenList) -> ParserResult<WithCursor<Expression>> {
+        let mut parser = Parser::create(tokens, self.source_file);
+
+        let Some(expr) = parser.expression()? else {
+            return Err(ParserErrorKind::ExpectedExpression);
         };
 
-        self.next();
-        let_expr!(rhs = self.expression()?);
+        if let Some(token) = parser.peek().filter(|t| t.kind != LexerTokenKind::EOF) {
+            return Err(ParserErrorKind::UnexpectedToken(token.kind.clone()));
+        }
 
-        Ok(Some(WithCursor::create_with(
-            lhs.start,
-            rhs.end,
-            Expression::Range(Box::from((lhs, rhs, inclusive))),
-        )))
+        Ok(expr)
     }
 
     // MARK: Grouping
@@ -597,6 +696,13 @@ impl<'a> Parser<'a> {
             return Err(ParserErrorKind::ExpectedExpression);
         };
 
+        // `else` may be placed on the line following the truthy block
+        if self.peek_is(&&LexerTokenKind::EOL)
+            && self.peek_nth(1).is_some_and(|t| t.kind == LexerTokenKind::Else)
+        {
+            self.next();
+        }
+
         let else_condition = if self.next_if_eq(&&LexerTokenKind::Else).is_some() {
             let start = self.cursor;
 
@@ -624,9 +730,10 @@ impl<'a> Parser<'a> {
     }
 
     fn stmt_if(&mut self) -> ParserResult<Option<Statement>> {
-        self.expect_token(&LexerTokenKind::If)?;
+        let start = self.expect_token(&LexerTokenKind::If)?.start;
 
-        let_expr!(expr = self.expr_if()?);
+        let_expr!(mut expr = self.expr_if()?);
+        expr.start = start;
 
         Ok(Some(Statement::If(Box::from(expr))))
     }
@@ -640,63 +747,63 @@ impl<'a> Parser<'a> {
         self.expect_token(&LexerTokenKind::LBracket)?;
         self.expect_terminator()?;
 
-        let mut hash_map = MatchCase::new();
+        let mut arms = MatchCase::new();
 
-        while let Some(token) = self.peek().cloned() {
-            if token.kind == LexerTokenKind::RBracket {
-                // self.next();
-                break;
-            }
-
-            if token.value.is_some() {
-                fn to_case(token: &LexerToken) -> ParserResult<WithCursor<Literal>> {
-                    Ok(WithCursor::create_with(
-                        token.start,
-                        token.end,
-                        token.to_owned().try_into()?,
-                    ))
+        while let Some(token) = self.peek() {
+            match token.kind {
+                LexerTokenKind::RBracket => {
+                    self.next();
+                    break;
                 }
+                LexerTokenKind::EOL => {
+                    self.next();
+                    continue;
+                }
+                _ => {}
+            }
 
-                let mut cases = vec![];
-
-                // First Case
-                cases.push(to_case(token)?);
+            let mut patterns = vec![self.match_pattern()?];
 
-                self.next();
-
-                while self.next_if_eq(&&LexerTokenKind::Or).is_some() {
-                    if let Some(token) = self.next() {
-                        cases.push(to_case(token)?)
-                    } else {
-                        break;
-                    }
-                }
+            while self.next_if_eq(&&LexerTokenKind::Or).is_some() {
+                patterns.push(self.match_pattern()?);
+            }
 
-                self.expect_token(&LexerTokenKind::Arrow)?;
+            self.expect_token(&LexerTokenKind::Arrow)?;
 
-                let_expr!(value = self.expression()?);
+            let_expr!(value = self.expression()?);
 
-                let rc = Rc::new(value);
+            arms.push(MatchArm { patterns, value });
 
-                for key in cases {
-                    hash_map.insert(key, rc.clone());
-                }
+            if self.peek().is_some_and(|t| t.kind != LexerTokenKind::RBracket) {
+                self.expect_terminator()?;
             }
-
-            self.next();
         }
 
         Ok(Some(WithCursor::create_with(
             start,
             self.cursor,
-            Expression::Match(Box::from((pattern, hash_map))),
+            Expression::Match(Box::from((pattern, arms))),
         )))
     }
 
+    fn match_pattern(&mut self) -> ParserResult<WithCursor<MatchPattern>> {
+        let Some(token) = self.next() else {
+            return Err(ParserErrorKind::UnexpectedEnd);
+        };
+
+        let pattern = match token.kind {
+            LexerTokenKind::Multiply => MatchPattern::Wildcard,
+            _ => MatchPattern::Literal(token.to_owned().try_into()?),
+        };
+
+        Ok(WithCursor::create_with(token.start, token.end, pattern))
+    }
+
     fn stmt_match(&mut self) -> ParserResult<Option<Statement>> {
-        self.expect_token(&LexerTokenKind::Match)?;
+        let start = self.expect_token(&LexerTokenKind::Match)?.start;
 
-        let_expr!(expr = self.expr_match()?);
+        let_expr!(mut expr = self.expr_match()?);
+        expr.start = start;
 
         Ok(Some(Statement::Match(Box::from(expr))))
     }
@@ -712,8 +819,10 @@ impl<'a> Parser<'a> {
             return Err(ParserErrorKind::ExpectedStatement);
         };
 
-        self.next();
-        Ok(WithCursor::create_with(start, self.cursor, vec![stmt]))
+        let mut block = vec![stmt];
+        mark_tail(&mut block);
+
+        Ok(WithCursor::create_with(start, self.cursor, block))
     }
 
     fn expr_block(&mut self) -> ParserResult<Option<WithCursor<Expression>>> {
@@ -735,6 +844,8 @@ impl<'a> Parser<'a> {
         let start = self.cursor;
 
         while let Some(token) = self.peek().cloned() {
+            block.extend(leading_comments(token));
+
             if token.kind == LexerTokenKind::RBracket {
                 self.next();
                 break;
@@ -747,15 +858,34 @@ impl<'a> Parser<'a> {
             self.next();
         }
 
+        mark_tail(&mut block);
+
         Ok(WithCursor::create_with(start, self.cursor, block))
     }
 
+    /// Returns the token `n` positions ahead without iterating
+    fn peek_nth(&self, n: usize) -> Option<&'a LexerToken> {
+        self.tokens.clone().nth(n)
+    }
+
     fn expect_token(&mut self, expected: &'a LexerTokenKind) -> ParserResult<&LexerToken> {
         self.expect(&expected).map_err(|found| {
             ParserErrorKind::ExpectedToken(vec![expected.clone()], found.map(|t| t.kind.clone()))
         })
     }
 
+    /// Checks that the statement is terminated without consuming the terminator,
+    /// as the caller is responsible for stepping over it
+    fn peek_terminator(&mut self) -> ParserResult<()> {
+        match self.peek().map(|t| t.kind.clone()) {
+            None | Some(LexerTokenKind::EOL | LexerTokenKind::EOF) => Ok(()),
+            Some(found) => Err(ParserErrorKind::ExpectedToken(
+                vec![LexerTokenKind::EOL, LexerTokenKind::EOF],
+                Some(found),
+            )),
+        }
+    }
+
     fn expect_terminator(&mut self) -> ParserResult<Option<&LexerToken>> {
         match self.expect_any(&[&LexerTokenKind::EOL, &LexerTokenKind::EOF]) {
             Ok(found) => Ok(Some(found)),
@@ -771,6 +901,32 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// Marks the expression a block ends with as its value, like Rust does.
+/// Assignments have no value, so they're left as they are
+fn mark_tail(block: &mut Block) {
+    // comments after the tail are left behind it
+    let end = ast::split_trailing_comments(block).0.len();
+    let comments = block.split_off(end);
+
+    if let Some(last) = block.pop() {
+        block.push(match last {
+            Statement::Expression(expr) | Statement::If(expr) | Statement::Match(expr) if !matches!(expr.value, Expression::Assignment(_)) => {
+                Statement::Tail(expr)
+            },
+            statement => statement,
+        });
+    }
+
+    block.extend(comments);
+}
+
+/// The comments written before the statement a token starts, the doc comments of a function are kept by it instead
+fn leading_comments(token: &LexerToken) -> impl Iterator<Item = Statement> + '_ {
+    token.trivia.iter()
+        .filter(move |comment| !(comment.doc && token.kind == LexerTokenKind::Function))
+        .map(|comment| Statement::Comment(Box::new(WithCursor::create_with(comment.start, comment.end, comment.text.clone()))))
+}
+
 // MARK: Comp Error
 impl ComponentErrors<ParserError> for Parser<'_> {
     fn fetch_errors(&self) -> &Vec<ParserError> {
@@ -1,11 +1,134 @@
+use std::fmt::Display;
+
+use crate::{cursor::{Cursor, WithCursor}, error::{CodeError, SourceFile}};
+
+use super::types::ValueType;
+
+/// The part of the source an error was raised for. Errors raised for a node without a
+/// position of their own leave it empty, and are placed at the nearest node with one
+pub type Span = Option<WithCursor<()>>;
+
 #[derive(thiserror::Error, lang_macro::EnumVariants, Debug)]
 pub enum TranspilerErrorKind {
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("fmt error: {0}")]
     Fmt(#[from] std::fmt::Error),
+    #[error("function '{name}' takes {expected} argument(s) but {found} were given")]
+    ArgumentCount {
+        name: String,
+        expected: String,
+        found: usize,
+        span: Span,
+    },
+    #[error("'{keyword}' can only be used inside of a loop")]
+    InvalidBreak {
+        keyword: &'static str,
+        span: Span,
+    },
+    #[error("'{name}' is declared twice in the same scope")]
+    Redeclared {
+        name: String,
+        span: Span,
+    },
+    #[error("{construct} can't be expressed in the target language")]
+    Unsupported {
+        construct: &'static str,
+        span: Span,
+    },
+    #[error("expected a value of type {expected} but found {found}")]
+    TypeMismatch {
+        expected: ValueType,
+        found: ValueType,
+        span: Span,
+    },
     #[error("unexpected error")]
     Unexpected
 }
 
-pub(super) type TranspilerResult<T> = std::result::Result<T, TranspilerErrorKind>;
\ No newline at end of file
+impl TranspilerErrorKind {
+    pub fn unsupported(construct: &'static str) -> Self {
+        Self::Unsupported { construct, span: None }
+    }
+
+    pub fn type_mismatch(expected: ValueType, found: ValueType) -> Self {
+        Self::TypeMismatch { expected, found, span: None }
+    }
+
+    pub fn span(&self) -> Option<&WithCursor<()>> {
+        match self {
+            Self::ArgumentCount { span, .. }
+            | Self::InvalidBreak { span, .. }
+            | Self::Redeclared { span, .. }
+            | Self::Unsupported { span, .. }
+            | Self::TypeMismatch { span, .. } => span.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Places the error at a node, unless it was placed at a node inside of it already
+    pub fn at<T>(mut self, node: &WithCursor<T>) -> Self {
+        if let Self::ArgumentCount { span, .. }
+            | Self::InvalidBreak { span, .. }
+            | Self::Redeclared { span, .. }
+            | Self::Unsupported { span, .. }
+            | Self::TypeMismatch { span, .. } = &mut self {
+            span.get_or_insert(node.span());
+        }
+
+        self
+    }
+}
+
+pub type TranspilerResult<T> = std::result::Result<T, TranspilerErrorKind>;
+
+/// Places the error of a failed lowering at the node that was being lowered
+pub trait Locate {
+    fn at<T>(self, node: &WithCursor<T>) -> Self;
+}
+
+impl<V> Locate for TranspilerResult<V> {
+    fn at<T>(self, node: &WithCursor<T>) -> Self {
+        self.map_err(|err| err.at(node))
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub struct TranspilerError {
+    pub kind: Box<TranspilerErrorKind>,
+    pub start: Cursor,
+    pub end: Cursor,
+    pub source_file: SourceFile,
+}
+
+impl Display for TranspilerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.format_error(f)
+    }
+}
+
+impl CodeError<TranspilerErrorKind> for TranspilerError {
+    fn kind(&self) -> &TranspilerErrorKind {
+        &self.kind
+    }
+
+    fn source_file(&self) -> &SourceFile {
+        &self.source_file
+    }
+
+    fn start(&self) -> &Cursor {
+        &self.start
+    }
+
+    fn end(&self) -> &Cursor {
+        &self.end
+    }
+}
+
+#[derive(thiserror::Error, lang_macro::EnumVariants, Debug, PartialEq, Eq)]
+pub enum SourceMapError {
+    #[error("missing the header of a source map")]
+    Header,
+    #[error("invalid entry on line {0}")]
+    Entry(usize),
+}
@@ -1,22 +1,101 @@
 mod targets;
 pub mod error;
+pub mod names;
+pub mod quote;
+pub mod source_map;
+pub mod types;
+pub mod writer;
 
-use error::TranspilerResult;
-use crate::parser::ast::{Expression, ProgramTree, Statement};
-pub use targets::bash::BashTranspiler;
+use std::{collections::HashSet, io::Write};
+use error::{TranspilerError, TranspilerErrorKind, TranspilerResult};
+use names::NameTable;
+use source_map::SourceMap;
+use types::{TypeTable, ValueType};
+use writer::{CodeWriter, Indent, MARK_END, MARK_START};
+use crate::{component::ComponentErrors, cursor::{Cursor, WithCursor}, error::SourceFile, parser::ast::{Expression, ProgramTree, Statement, Variable}};
+pub use targets::{bash::BashTranspiler, fish::FishTranspiler, powershell::PowerShellTranspiler};
 
 // MARK: Transpilers
-#[derive(Debug, PartialEq, Default)]
+#[derive(Default)]
 pub enum TranspilerTarget {
     #[default]
     Bash,
+    /// POSIX sh, for shells like dash and busybox ash
+    Posix,
+    PowerShell,
+    Fish,
+    /// A target implemented outside of this crate
+    Custom(Box<dyn Target>),
 }
 
 impl TranspilerTarget {
-    fn get_impl(&self) -> Box<dyn TranspilerImpl> {
-        Box::from(match self {
-            Self::Bash => BashTranspiler::default(),
-        })
+    pub fn custom(target: impl Target + 'static) -> Self {
+        Self::Custom(Box::new(target))
+    }
+}
+
+impl Target for TranspilerTarget {
+    fn name(&self) -> &str {
+        match self {
+            Self::Bash => "bash",
+            Self::Posix => "sh",
+            Self::PowerShell => "powershell",
+            Self::Fish => "fish",
+            Self::Custom(target) => target.name(),
+        }
+    }
+
+    fn options(&self) -> TranspilerOptions {
+        let shebang = match self {
+            Self::Bash => "/usr/bin/env bash",
+            Self::Posix => "/bin/sh",
+            Self::PowerShell => "/usr/bin/env pwsh",
+            Self::Fish => "/usr/bin/env fish",
+            Self::Custom(target) => return target.options(),
+        };
+
+        TranspilerOptions {
+            shebang: Some(shebang.to_string()),
+            // fish stops at nothing, there is no flag for it
+            strict: *self != Self::Fish,
+            safe_ifs: false,
+            header: true,
+            source_map: false,
+            debug: false,
+            indent: Indent::Spaces(4),
+            blank_lines: true,
+            comments: true,
+            minify: false,
+        }
+    }
+
+    fn create<'a>(&self, ctx: TranspilerContext) -> Box<dyn TranspilerImpl<'a>> {
+        match self {
+            Self::Bash => Box::new(BashTranspiler::create(ctx)),
+            Self::Posix => Box::new(BashTranspiler::posix(ctx)),
+            Self::PowerShell => Box::new(PowerShellTranspiler::create(ctx)),
+            Self::Fish => Box::new(FishTranspiler::create(ctx)),
+            Self::Custom(target) => target.create(ctx),
+        }
+    }
+}
+
+impl std::fmt::Debug for TranspilerTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bash => f.write_str("Bash"),
+            Self::Posix => f.write_str("Posix"),
+            Self::PowerShell => f.write_str("PowerShell"),
+            Self::Fish => f.write_str("Fish"),
+            Self::Custom(target) => f.debug_tuple("Custom").field(&target.name()).finish(),
+        }
+    }
+}
+
+/// Targets are the same when they have the same name
+impl PartialEq for TranspilerTarget {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
     }
 }
 
@@ -24,45 +103,420 @@ impl TranspilerTarget {
 pub struct Transpiler<'a> {
     tree: &'a ProgramTree,
     inner: Box<dyn TranspilerImpl<'a>>,
-    out: String
+    errors: Vec<TranspilerError>,
+    source_file: &'a SourceFile,
+    source_map: SourceMap,
 }
 
 impl<'a> Transpiler<'a> {
-    pub fn create(target: &'a TranspilerTarget, tree: &'a ProgramTree) -> Self {
+    /// Creates a transpiler with the default options of the target
+    pub fn create(target: &dyn Target, tree: &'a ProgramTree, source_file: &'a SourceFile) -> Self {
+        Self::create_with(target, target.options(), tree, source_file)
+    }
+
+    pub fn create_with(target: &dyn Target, mut options: TranspilerOptions, tree: &'a ProgramTree, source_file: &'a SourceFile) -> Self {
+        let mut ctx = TranspilerContext::create(TypeTable::create(tree));
+
+        if options.minify {
+            options.comments = false;
+            options.header = false;
+            options.indent = Indent::None;
+            options.blank_lines = false;
+            ctx.names = NameTable::create(tree);
+        }
+
+        ctx.options = options;
+
         Self {
             tree,
-            inner: target.get_impl(),
-            out: String::new()
+            inner: target.create(ctx),
+            errors: Vec::new(),
+            source_file,
+            source_map: SourceMap::create(source_file.get_path()),
         }
     }
     
-    pub fn transpile(&mut self) -> &String {
-        if !self.out.is_empty() {
-            return &self.out;
+    /// Writes the program to `out` a statement at a time, as it's lowered. A statement that fails
+    /// to lower is left out and its error is collected, so the output of a program with errors is incomplete.
+    ///
+    /// The prelude of a debug build needs to know the lines of the whole program, so the program is
+    /// lowered into a buffer first and written after the prelude
+    pub fn transpile_to(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        self.errors.clear();
+        self.source_map = SourceMap::create(self.source_file.get_path());
+        let indent = self.inner.ctx().options.indent;
+
+        if self.inner.ctx().options.debug {
+            let mut program = Vec::new();
+            self.transpile_program(&mut CodeWriter::create(indent), &mut program)?;
+
+            let lines = self.write_prelude(&mut CodeWriter::create(indent), out)?;
+            self.source_map.offset(lines);
+            out.write_all(&program)?;
+        } else {
+            let mut writer = CodeWriter::create(indent);
+            self.write_prelude(&mut writer, out)?;
+            self.transpile_program(&mut writer, out)?;
+        }
+
+        out.flush()
+    }
+
+    /// Writes the prelude followed by an empty line unless minifying, returns how many lines were written
+    fn write_prelude(&mut self, writer: &mut CodeWriter, out: &mut impl Write) -> std::io::Result<usize> {
+        match self.inner.transpile_prelude(self.source_file, &self.source_map) {
+            Ok(prelude) if prelude.is_empty() => Ok(0),
+            Ok(prelude) => {
+                let separator = self.inner.ctx().options.prelude_separator();
+
+                self.write_code(writer, out, &format!("{prelude}{separator}"))?;
+                Ok(writer.line() - 1)
+            },
+            Err(err) => {
+                self.add_error(err);
+                Ok(0)
+            },
         }
-        
+    }
+
+    fn transpile_program(&mut self, writer: &mut CodeWriter, out: &mut impl Write) -> std::io::Result<()> {
+        let mut previous: Option<WithCursor<()>> = None;
+
         for statement in self.tree {
+            let span = statement.span();
+
+            // blank lines between the statements of the source are kept between them
+            if let Some(previous) = previous {
+                if self.inner.ctx().options.blank_lines
+                    && writer::has_blank_line(self.source_file.get_code(), previous.end.index(), span.start.index()) {
+                    self.write_code(writer, out, "\n")?;
+                }
+            }
+            previous = Some(span);
+
+            let mark = self.inner.ctx_mut().mark(statement);
+
             match self.inner.transpile_stmt(statement) {
-                Ok(out) => self.out.push_str(out.as_str()),
-                Err(err) => error!("{err:#?}")
+                Ok(code) => self.write_code(writer, out, &format!("{mark}{code}"))?,
+                Err(err) => {
+                    debug!("adding error {err:?}");
+                    self.add_error(err.at(&span))
+                }
             }
         }
-        
-        &self.out
+
+        Ok(())
+    }
+
+    /// Lowers the whole program into a string
+    pub fn transpile(&mut self) -> String {
+        let mut out = Vec::new();
+        self.transpile_to(&mut out).expect("writing to a vec never fails");
+
+        String::from_utf8(out).expect("the lowering only writes strings")
+    }
+
+    /// Which lines of the output the statements were lowered to, recorded when the
+    /// program is written with [`TranspilerOptions::source_map`] set
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// Writes code to the output, taking the marks of the statements out of it to record their lines
+    fn write_code(&mut self, writer: &mut CodeWriter, out: &mut impl Write, code: &str) -> std::io::Result<()> {
+        for (line, id) in writer.write(out, code)? {
+            self.source_map.add(line, self.inner.ctx().marked(id));
+        }
+
+        Ok(())
+    }
+
+    fn add_error(&mut self, kind: TranspilerErrorKind) {
+        // errors without a span, such as io errors, are shown at the start of the file
+        let (start, end) = match kind.span() {
+            Some(span) => (span.start, span.end),
+            None => (Cursor::create(), Cursor::create()),
+        };
+
+        self.errors.push(TranspilerError {
+            start,
+            end,
+            kind: Box::new(kind),
+            source_file: self.source().sliced(start, end),
+        });
+    }
+}
+
+impl ComponentErrors<TranspilerError> for Transpiler<'_> {
+    fn fetch_errors(&self) -> &Vec<TranspilerError> {
+        &self.errors
+    }
+
+    fn source(&self) -> &SourceFile {
+        self.source_file
+    }
+}
+
+// MARK: Options
+/// What is written around the program itself. The default is the program alone,
+/// each target picks its own defaults through [`Target::options`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranspilerOptions {
+    /// The interpreter written after `#!` on the first line
+    pub shebang: Option<String>,
+    /// Stops the script at the first failing command, or at the use of an unset variable
+    pub strict: bool,
+    /// Splits words only on newlines and tabs, so values with spaces survive being expanded
+    /// unquoted. This includes the values of `#*{}`, which then aren't split on spaces either
+    pub safe_ifs: bool,
+    /// Marks the script as generated, with the path of the file it was generated from
+    pub header: bool,
+    /// Records the line of the output every statement is lowered to, see [`Transpiler::source_map`]
+    pub source_map: bool,
+    /// Traces every command the script runs along with the line of the source it was lowered from
+    pub debug: bool,
+    /// What the blocks nested in others are indented with
+    pub indent: Indent,
+    /// Keeps the empty lines between the top level statements of the source
+    pub blank_lines: bool,
+    /// Keeps the comments of the source, and writes the doc comments of a function as its usage above it
+    pub comments: bool,
+    /// Writes the script as small as it gets, for places with a limit on its size. Comments, the header,
+    /// indentation and blank lines are left out, and the names the program declares are shortened, see [`names`].
+    /// Names are only shortened within the script, so scripts sharing names through `@include` can't be minified
+    pub minify: bool,
+}
+
+impl TranspilerOptions {
+    /// What is written between the prelude and the program
+    pub(crate) fn prelude_separator(&self) -> &'static str {
+        if self.minify { "" } else { "\n" }
     }
 }
 
 // MARK: Context
-#[derive(Default)]
+/// State of the lowering, which changes as it steps in and out of blocks
 pub struct TranspilerContext {
-    pub scope_depth: i8,
-    pub in_loop: bool,
+    pub types: TypeTable,
+    pub options: TranspilerOptions,
+    /// The short names of a minified script
+    pub names: NameTable,
+    /// Open scopes from the outermost, the first one being the program itself
+    scopes: Vec<Scope>,
+    /// Spans of the statements marked in the output
+    marks: Vec<WithCursor<()>>,
+}
+
+/// What opened a scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeKind {
+    Block,
+    Loop,
+    Function(String),
+}
+
+struct Scope {
+    kind: ScopeKind,
+    names: HashSet<String>,
+}
+
+impl Default for TranspilerContext {
+    fn default() -> Self {
+        Self::create(TypeTable::default())
+    }
+}
+
+impl TranspilerContext {
+    pub fn create(types: TypeTable) -> Self {
+        Self {
+            types,
+            options: TranspilerOptions::default(),
+            names: NameTable::default(),
+            scopes: vec![Scope { kind: ScopeKind::Block, names: HashSet::new() }],
+            marks: Vec::new(),
+        }
+    }
+
+    pub fn push_scope(&mut self, kind: ScopeKind) {
+        self.scopes.push(Scope { kind, names: HashSet::new() });
+    }
+
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// Marks where the lowering of a statement starts, to be written in front of its code. Once the
+    /// code is written out the mark is replaced by an entry of the source map, which is how the line
+    /// of a statement nested in others is found. Empty when no source map is recorded
+    pub fn mark(&mut self, statement: &Statement) -> String {
+        if !(self.options.source_map || self.options.debug) {
+            return String::new();
+        }
+
+        self.marks.push(statement.span());
+        format!("{MARK_START}{}{MARK_END}", self.marks.len() - 1)
+    }
+
+    fn marked(&self, id: usize) -> WithCursor<()> {
+        self.marks[id]
+    }
+
+    /// The name a variable is written with, which is shorter in a minified script
+    pub fn variable_name(&self, name: &str) -> String {
+        self.names.variable(name).unwrap_or(name).to_string()
+    }
+
+    /// The name a function is written with, which is shorter in a minified script
+    pub fn function_name(&self, name: &str) -> String {
+        self.names.function(name).unwrap_or(name).to_string()
+    }
+
+    /// Whether `break` and `continue` have a loop to act on. A loop outside of
+    /// the function being transpiled doesn't count, as the body runs on its own
+    pub fn in_loop(&self) -> bool {
+        self.scopes.iter().rev()
+            .map(|scope| &scope.kind)
+            .take_while(|kind| !matches!(kind, ScopeKind::Function(_)))
+            .any(|kind| *kind == ScopeKind::Loop)
+    }
+
+    /// The name of the innermost function being transpiled
+    pub fn function(&self) -> Option<&str> {
+        self.scopes.iter().rev().find_map(|scope| match &scope.kind {
+            ScopeKind::Function(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The return type of the innermost function being transpiled
+    pub fn return_type(&self) -> ValueType {
+        self.function()
+            .and_then(|name| self.types.function(name))
+            .map(|func| func.return_type)
+            .unwrap_or_default()
+    }
+
+    /// Records a variable declared in the current scope, where a name can only be declared once
+    pub fn declare(&mut self, name: &str) -> TranspilerResult<()> {
+        let scope = self.scopes.last_mut().expect("the program scope is never closed");
+
+        if !scope.names.insert(name.to_string()) {
+            return Err(TranspilerErrorKind::Redeclared { name: name.to_string(), span: None });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a call to a function declared in the program passes an argument for every
+    /// parameter without a default value, no more arguments than there are parameters, and
+    /// arguments of the types of the parameters
+    pub fn check_call(&self, name: &str, args: &[WithCursor<Expression>]) -> TranspilerResult<()> {
+        let Some(func) = self.types.function(name) else {
+            return Ok(());
+        };
+
+        if !func.accepts(args.len()) {
+            let expected = if func.required == func.parameters.len() {
+                func.required.to_string()
+            } else {
+                format!("{} to {}", func.required, func.parameters.len())
+            };
+
+            return Err(TranspilerErrorKind::ArgumentCount {
+                name: name.to_string(),
+                expected,
+                found: args.len(),
+                span: None,
+            });
+        }
+
+        for (expected, arg) in func.parameters.iter().zip(args) {
+            self.check_type(*expected, arg)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the value of a variable matches its type annotation
+    pub fn check_var(&self, var: &Variable) -> TranspilerResult<()> {
+        match (&var.strict_type, &var.value) {
+            (Some(strict_type), Some(value)) => self.check_type(ValueType::from_name(strict_type), value),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a value is of the expected type, values of a type
+    /// only known when the script runs are expected to match
+    pub fn check_type(&self, expected: ValueType, value: &WithCursor<Expression>) -> TranspilerResult<()> {
+        let found = self.types.infer(&value.value);
+
+        if expected == ValueType::Unknown || found == ValueType::Unknown || expected == found {
+            return Ok(());
+        }
+
+        Err(TranspilerErrorKind::TypeMismatch {
+            expected,
+            found,
+            span: Some(value.span()),
+        })
+    }
 }
 
 // MARK: Definition
+/// A target language, which creates a [`TranspilerImpl`] for every program lowered to it.
+/// Crates can implement their own and pass it around as a [`TranspilerTarget::Custom`]
+pub trait Target {
+    fn name(&self) -> &str;
+
+    /// The options a program is lowered with unless others are given, scripts stop at the first error by default
+    fn options(&self) -> TranspilerOptions {
+        TranspilerOptions {
+            strict: true,
+            header: true,
+            ..Default::default()
+        }
+    }
+
+    fn create<'a>(&self, ctx: TranspilerContext) -> Box<dyn TranspilerImpl<'a>>;
+}
+
+/// Features that a target language may lack, so the lowering can work around them or fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    /// Variables can hold a list of values, which can be iterated
+    pub arrays: bool,
+    /// Arithmetic isn't limited to integers
+    pub floats: bool,
+    /// Functions can declare variables that aren't visible to their caller
+    pub local_variables: bool,
+}
+
+/// The lowering of a program to a target language.
+///
+/// Functions pass their results back the way shells do: `Bool` results through the exit status,
+/// so calls can be used as conditions directly, and every other value through stdout, which the
+/// call site captures when the call is used as a value
 pub trait TranspilerImpl<'a> {
     fn ctx(&self) -> &TranspilerContext;
+    fn ctx_mut(&mut self) -> &mut TranspilerContext;
+    fn capabilities(&self) -> Capabilities;
 
-    fn transpile_stmt(&self, statement: &'a Statement) -> TranspilerResult<String>;
-    fn transpile_expr(&self, expression: &'a Expression) -> TranspilerResult<String>;
+    /// Lowers what comes before the program, as set in the options of the context. In a debug
+    /// build the source map holds the lines of the program, counted from the end of the prelude
+    fn transpile_prelude(&mut self, _source_file: &SourceFile, _source_map: &SourceMap) -> TranspilerResult<String> {
+        Ok(String::new())
+    }
+
+    fn transpile_stmt(&mut self, statement: &'a Statement) -> TranspilerResult<String>;
+    fn transpile_expr(&mut self, expression: &'a Expression) -> TranspilerResult<String>;
+
+    /// Runs part of the lowering inside of a new scope, which is closed again even if it fails
+    fn scoped<T>(&mut self, kind: ScopeKind, lower: impl FnOnce(&mut Self) -> TranspilerResult<T>) -> TranspilerResult<T> where Self: Sized {
+        self.ctx_mut().push_scope(kind);
+        let result = lower(self);
+        self.ctx_mut().pop_scope();
+        result
+    }
 }
\ No newline at end of file
@@ -0,0 +1,253 @@
+//! Shorter names for the variables and functions of a minified script.
+//!
+//! Only the names the program declares itself are shortened. A name is left as it is when it's also
+//! written out in the text of a command, where it can't be renamed along with the program, e.g.
+//! `$trap cleanup EXIT`, and when it's written in uppercase, which is how environment variables are written
+
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::ast::{Block, Expression, ProgramTree, ShellCommand, ShellWordPart, Statement, StringPart};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NameTable {
+    variables: HashMap<String, String>,
+    functions: HashMap<String, String>,
+}
+
+impl NameTable {
+    pub fn create(tree: &ProgramTree) -> Self {
+        let mut names = Names::default();
+        names.collect_block(tree);
+
+        let keep = |name: &String| names.words.contains(name) || is_environment(name);
+
+        // shells like PowerShell don't tell apart names written in another case
+        let mut taken: HashSet<String> = names.referenced.iter()
+            .filter(|name| !names.variables.contains(name) && !names.functions.contains(name))
+            .chain(&names.words)
+            .chain(names.variables.iter().chain(&names.functions).filter(|name| keep(name)))
+            .map(|name| name.to_lowercase())
+            .collect();
+
+        let mut table = Self::default();
+        let mut index = 0;
+
+        for name in names.variables.iter().filter(|name| !keep(name)) {
+            table.variables.insert(name.clone(), next_name(&mut taken, &mut index, ""));
+        }
+
+        // functions start with an underscore, so they never hide a command or an alias
+        index = 0;
+        for name in names.functions.iter().filter(|name| !keep(name)) {
+            table.functions.insert(name.clone(), next_name(&mut taken, &mut index, "_"));
+        }
+
+        table
+    }
+
+    /// The short name of a variable, if the program declares it
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
+
+    /// The short name of a function, if the program declares it
+    pub fn function(&self, name: &str) -> Option<&str> {
+        self.functions.get(name).map(String::as_str)
+    }
+}
+
+/// Environment variables are written in uppercase, e.g. `PATH` or `XDG_CONFIG_HOME`
+fn is_environment(name: &str) -> bool {
+    name.chars().all(|char| char.is_ascii_uppercase() || char.is_ascii_digit() || char == '_')
+}
+
+/// The next name of `a`, `b`, ... `z`, `aa`, `ab`, ... which isn't taken yet
+fn next_name(taken: &mut HashSet<String>, index: &mut usize, prefix: &str) -> String {
+    loop {
+        let mut name = String::new();
+        let mut rest = *index;
+        *index += 1;
+
+        loop {
+            name.insert(0, char::from(b'a' + (rest % 26) as u8));
+
+            if rest < 26 {
+                break;
+            }
+
+            rest = rest / 26 - 1;
+        }
+
+        let name = format!("{prefix}{name}");
+
+        if taken.insert(name.clone()) {
+            return name;
+        }
+    }
+}
+
+/// The names a program uses, in the order they're declared in
+#[derive(Default)]
+struct Names {
+    variables: Vec<String>,
+    functions: Vec<String>,
+    /// Identifiers the program uses, which are told apart from the declared ones
+    /// once everything is collected, as names can be used before they're declared
+    referenced: HashSet<String>,
+    /// Words of the text of commands, including their names
+    words: HashSet<String>,
+}
+
+impl Names {
+    fn declare_variable(&mut self, name: &str) {
+        if !self.variables.iter().any(|declared| declared == name) {
+            self.variables.push(name.to_string());
+        }
+    }
+
+    fn declare_function(&mut self, name: &str) {
+        if !self.functions.iter().any(|declared| declared == name) {
+            self.functions.push(name.to_string());
+        }
+    }
+
+    fn reference(&mut self, name: &str) {
+        self.referenced.insert(name.to_string());
+    }
+
+    fn add_words(&mut self, text: &str) {
+        let words = text.split(|char: char| !char.is_alphanumeric() && char != '_')
+            .filter(|word| !word.is_empty())
+            .map(str::to_string);
+
+        self.words.extend(words);
+    }
+
+    fn collect_block(&mut self, block: &Block) {
+        for statement in block {
+            self.collect_stmt(statement);
+        }
+    }
+
+    fn collect_stmt(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Variable(var) | Statement::Constant(var) => {
+                let var = &var.value;
+                self.declare_variable(&var.name);
+
+                if let Some(value) = &var.value {
+                    self.collect_expr(&value.value);
+                }
+            },
+            Statement::Function(func) => {
+                let func = &func.value;
+                self.declare_function(&func.name);
+
+                for param in func.parameters.iter().flatten() {
+                    self.declare_variable(&param.name);
+
+                    if let Some(default) = &param.value {
+                        self.collect_expr(&default.value);
+                    }
+                }
+
+                self.collect_block(&func.body.value);
+            },
+            Statement::For(for_loop) => {
+                let (variable, iterable, block) = &for_loop.value;
+
+                self.declare_variable(&variable.name);
+                self.collect_expr(&iterable.value);
+                self.collect_block(&block.value);
+            },
+            Statement::While(while_loop) => {
+                let (condition, block) = &while_loop.value;
+
+                self.collect_expr(&condition.value);
+                self.collect_block(&block.value);
+            },
+            Statement::If(expr) | Statement::Match(expr) | Statement::Expression(expr) | Statement::Tail(expr) => self.collect_expr(&expr.value),
+            Statement::Return(value) => {
+                if let Some(value) = &value.value {
+                    self.collect_expr(&value.value);
+                }
+            },
+            Statement::Continue(_) | Statement::Break(_) | Statement::Include(_) | Statement::Comment(_) => {},
+        }
+    }
+
+    fn collect_expr(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Literal(_) => {},
+            Expression::Identifier(identifier) => self.reference(identifier),
+            Expression::Group(expr) => self.collect_expr(expr),
+            Expression::Unary(unary) => self.collect_expr(&unary.1.value),
+            Expression::Arithmetic(arithmetic) => {
+                self.collect_expr(&arithmetic.0.value);
+                self.collect_expr(&arithmetic.2.value);
+            },
+            Expression::Logical(logical) => {
+                self.collect_expr(&logical.0.value);
+                self.collect_expr(&logical.2.value);
+            },
+            Expression::Assignment(assignment) => {
+                self.collect_expr(&assignment.0.value);
+                self.collect_expr(&assignment.2.value);
+            },
+            Expression::Range(range) => {
+                self.collect_expr(&range.0.value);
+                self.collect_expr(&range.1.value);
+            },
+            Expression::ShellCommand(cmd) => self.collect_command(cmd),
+            Expression::InterpolatedString(parts) => {
+                for part in parts.iter() {
+                    if let StringPart::Expression(expr) = part {
+                        self.collect_expr(&expr.value);
+                    }
+                }
+            },
+            Expression::FunctionCall(call) => {
+                self.reference(&call.0);
+
+                for arg in &call.1 {
+                    self.collect_expr(&arg.value);
+                }
+            },
+            Expression::If(if_expr) => {
+                self.collect_expr(&if_expr.0.value);
+                self.collect_block(&if_expr.1.value);
+
+                if let Some(else_block) = &if_expr.2 {
+                    self.collect_block(&else_block.value);
+                }
+            },
+            Expression::Match(match_expr) => {
+                self.collect_expr(&match_expr.0.value);
+
+                for arm in &match_expr.1 {
+                    self.collect_expr(&arm.value.value);
+                }
+            },
+            Expression::Block(block) => self.collect_block(block),
+        }
+    }
+
+    fn collect_command(&mut self, command: &ShellCommand) {
+        self.add_words(&command.name);
+
+        for part in command.args.iter().flatten() {
+            match part {
+                ShellWordPart::Literal(text) | ShellWordPart::SingleQuoted(text) => self.add_words(text),
+                ShellWordPart::DoubleQuoted(parts) => {
+                    for part in parts {
+                        match part {
+                            StringPart::Literal(text) => self.add_words(text),
+                            StringPart::Expression(expr) => self.collect_expr(&expr.value),
+                        }
+                    }
+                },
+                ShellWordPart::Interpolation(expr) | ShellWordPart::UnquotedInterpolation(expr) => self.collect_expr(&expr.value),
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+/// Quoting rules of a target shell. Every piece of literal text coming from a script
+/// goes through these, so a value can never be read back as code or split into several words
+pub trait Quoting {
+    /// Quotes text so the shell reads it back as a single word, exactly as it is
+    fn quote(text: &str) -> String;
+
+    /// Escapes text so it can be placed inside of a double quoted string without being expanded
+    fn escape_double_quoted(text: &str) -> String;
+
+    /// Quotes text while leaving the glob characters `*`, `?` and `[...]` to be expanded by the shell
+    fn quote_glob(text: &str) -> String;
+}
+
+/// Quoting shared by `sh` and its descendants
+pub struct PosixQuoting;
+
+impl Quoting for PosixQuoting {
+    /// Single quotes disable every expansion, a single quote itself is written as `'\''`
+    fn quote(text: &str) -> String {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+
+    fn escape_double_quoted(text: &str) -> String {
+        let mut s = String::with_capacity(text.len());
+
+        for char in text.chars() {
+            if matches!(char, '\\' | '"' | '$' | '`') {
+                s.push('\\');
+            }
+
+            s.push(char);
+        }
+
+        s
+    }
+
+    fn quote_glob(text: &str) -> String {
+        let mut s = String::with_capacity(text.len());
+        let mut literal = String::new();
+
+        let mut chars = text.chars();
+
+        while let Some(char) = chars.next() {
+            if !matches!(char, '*' | '?' | '[') {
+                literal.push(char);
+                continue;
+            }
+
+            if !literal.is_empty() {
+                s.push_str(&Self::quote(&std::mem::take(&mut literal)));
+            }
+
+            s.push(char);
+
+            // a bracket expression is kept as a whole, as quoting would make its ranges literal
+            if char == '[' {
+                for char in chars.by_ref() {
+                    s.push(char);
+
+                    if char == ']' {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !literal.is_empty() {
+            s.push_str(&Self::quote(&literal));
+        }
+
+        s
+    }
+}
+
+/// Quoting of PowerShell, which also treats the typographic quotes as quotes
+pub struct PowerShellQuoting;
+
+impl Quoting for PowerShellQuoting {
+    /// Single quotes disable every expansion, a single quote itself is written twice
+    fn quote(text: &str) -> String {
+        let mut s = String::with_capacity(text.len() + 2);
+        s.push('\'');
+
+        for char in text.chars() {
+            if matches!(char, '\'' | '‘' | '’' | '‚' | '‛') {
+                s.push(char);
+            }
+
+            s.push(char);
+        }
+
+        s.push('\'');
+        s
+    }
+
+    fn escape_double_quoted(text: &str) -> String {
+        let mut s = String::with_capacity(text.len());
+
+        for char in text.chars() {
+            if matches!(char, '`' | '"' | '$' | '“' | '”' | '„') {
+                s.push('`');
+            }
+
+            s.push(char);
+        }
+
+        s
+    }
+
+    /// Cmdlets taking a path expand the wildcards themselves, so the pattern is passed as it is
+    fn quote_glob(text: &str) -> String {
+        Self::quote(text)
+    }
+}
+
+/// Quoting of fish, where a backslash keeps its meaning inside of single quotes
+pub struct FishQuoting;
+
+impl Quoting for FishQuoting {
+    /// Single quotes disable every expansion, a single quote and a backslash are escaped with a backslash
+    fn quote(text: &str) -> String {
+        let mut s = String::with_capacity(text.len() + 2);
+        s.push('\'');
+
+        for char in text.chars() {
+            if matches!(char, '\'' | '\\') {
+                s.push('\\');
+            }
+
+            s.push(char);
+        }
+
+        s.push('\'');
+        s
+    }
+
+    fn escape_double_quoted(text: &str) -> String {
+        let mut s = String::with_capacity(text.len());
+
+        for char in text.chars() {
+            if matches!(char, '\\' | '"' | '$') {
+                s.push('\\');
+            }
+
+            s.push(char);
+        }
+
+        s
+    }
+
+    /// Only `*` is a wildcard in fish, `?` and `[...]` are quoted as literal text
+    fn quote_glob(text: &str) -> String {
+        text.split('*')
+            .map(|literal| if literal.is_empty() { String::new() } else { Self::quote(literal) })
+            .collect::<Vec<_>>()
+            .join("*")
+    }
+}
@@ -0,0 +1,124 @@
+//! Maps the lines of a transpiled script back to the source they were lowered from.
+//!
+//! The map is written next to the script as `<script>.map`, a text file with one entry per line:
+//!
+//! ```text
+//! tsh-source-map 1
+//! source /home/user/deploy.tsh
+//! 5 1:13 1:18
+//! 6 2:1 4:2
+//! 7 3:5 3:19
+//! ```
+//!
+//! The first line holds the version of the format, the second one the path of the source.
+//! Every entry after them holds the line of the script a statement starts at, followed by the
+//! start and the end of the statement in the source as `line:col`. Lines and columns count from 1,
+//! entries are ordered by their line, and a line without an entry belongs to the entry before it
+
+use std::io::Write;
+
+use crate::cursor::{Cursor, WithCursor};
+
+use super::error::SourceMapError;
+
+const HEADER: &str = "tsh-source-map 1";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SourceMap {
+    source: String,
+    /// The line of the script a statement starts at and the span of the statement
+    entries: Vec<(usize, WithCursor<()>)>,
+}
+
+impl SourceMap {
+    pub fn create(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// The path of the source the script was transpiled from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Records that a statement starts at a line of the script. Statements nested in one
+    /// on the same line are recorded after it, so the innermost one is kept
+    pub fn add(&mut self, line: usize, span: WithCursor<()>) {
+        match self.entries.last_mut() {
+            Some(entry) if entry.0 == line => entry.1 = span,
+            _ => self.entries.push((line, span)),
+        }
+    }
+
+    /// The last line of the script a statement starts at
+    pub fn lines(&self) -> usize {
+        self.entries.last().map(|entry| entry.0).unwrap_or_default()
+    }
+
+    /// Moves the entries down by a number of lines, for code written in front of the script
+    pub(super) fn offset(&mut self, lines: usize) {
+        for entry in &mut self.entries {
+            entry.0 += lines;
+        }
+    }
+
+    /// The span of the statement a line of the script was lowered from
+    pub fn lookup(&self, line: usize) -> Option<&WithCursor<()>> {
+        let index = self.entries.partition_point(|entry| entry.0 <= line);
+        self.entries[..index].last().map(|entry| &entry.1)
+    }
+
+    pub fn write_to(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out, "{HEADER}")?;
+        writeln!(out, "source {}", self.source)?;
+
+        for (line, span) in &self.entries {
+            writeln!(out, "{line} {}:{} {}:{}", span.start.line(), span.start.col(), span.end.line(), span.end.col())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SourceMapError> {
+        let mut lines = text.lines();
+
+        if lines.next() != Some(HEADER) {
+            return Err(SourceMapError::Header);
+        }
+
+        let source = lines.next()
+            .and_then(|line| line.strip_prefix("source "))
+            .ok_or(SourceMapError::Header)?;
+
+        let mut map = Self::create(source);
+
+        // the entries start at the third line of the file
+        for (index, line) in lines.enumerate() {
+            let entry = Self::parse_entry(line).ok_or(SourceMapError::Entry(index + 3))?;
+            map.entries.push(entry);
+        }
+
+        Ok(map)
+    }
+
+    fn parse_entry(line: &str) -> Option<(usize, WithCursor<()>)> {
+        let mut fields = line.split(' ');
+
+        let output_line = fields.next()?.parse().ok()?;
+        let start = Self::parse_cursor(fields.next()?)?;
+        let end = Self::parse_cursor(fields.next()?)?;
+
+        if fields.next().is_some() {
+            return None;
+        }
+
+        Some((output_line, WithCursor::create_with(start, end, ())))
+    }
+
+    fn parse_cursor(field: &str) -> Option<Cursor> {
+        let (line, col) = field.split_once(':')?;
+        Some(Cursor::from(line.parse().ok()?, col.parse().ok()?))
+    }
+}
@@ -1,9 +1,17 @@
 use std::fmt::Write;
-use crate::{parser::ast::{Expression, Literal, Statement, Variable}, transpiler::{error::TranspilerResult, TranspilerContext, TranspilerImpl}};
+use crate::{cursor::WithCursor, error::SourceFile, parser::ast::{ArithmeticOperator, AssignmentOperator, Block, Else, Expression, Function, InterpolatedString, Literal, LogicalOperator, MatchCase, MatchPattern, ShellCommand, ShellWordPart, Statement, StringPart, UnaryOperator, Variable}, transpiler::{error::{Locate, TranspilerErrorKind, TranspilerResult}, quote::{PosixQuoting, Quoting}, types::ValueType, source_map::SourceMap, writer::{indented, verbatim}, Capabilities, ScopeKind, TranspilerContext, TranspilerImpl}};
 
+use super::{comment, has_effects, include_path, is_glob, literal_text, prelude_header, usage_header, unwrap_group, ShellLowering};
+
+/// Matches any value with a character that can't be part of a number
+const NOT_INTEGER: &str = "*[!0-9-]*";
+
+/// Transpiles to bash, or to plain POSIX sh for shells like dash and busybox ash,
+/// in which case every bash extension is avoided
 #[derive(Default)]
 pub struct BashTranspiler {
     ctx: TranspilerContext,
+    posix: bool,
 }
 
 impl<'a> TranspilerImpl<'a> for BashTranspiler {
@@ -11,31 +19,336 @@ impl<'a> TranspilerImpl<'a> for BashTranspiler {
         &self.ctx
     }
 
-    fn transpile_stmt(&self, statement: &'a Statement) -> TranspilerResult<String> {
+    fn ctx_mut(&mut self) -> &mut TranspilerContext {
+        &mut self.ctx
+    }
+
+    /// `local` isn't part of POSIX, but dash and busybox ash both have it
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            arrays: !self.posix,
+            floats: false,
+            local_variables: true,
+        }
+    }
+
+    fn transpile_prelude(&mut self, source_file: &SourceFile, source_map: &SourceMap) -> TranspilerResult<String> {
+        let options = &self.ctx.options;
+        let mut s = prelude_header(options, source_file);
+
+        match options.strict {
+            // `pipefail` isn't part of POSIX
+            true if self.posix => writeln!(s, "set -eu")?,
+            true => writeln!(s, "set -euo pipefail")?,
+            false => {},
+        }
+
+        match options.safe_ifs {
+            // sh has no `$'...'`, so the newline and the tab are written as they are
+            true if self.posix => writeln!(s, "IFS='\n\t'")?,
+            true => writeln!(s, "IFS=$'\\n\\t'")?,
+            false => {},
+        }
+
+        if options.debug {
+            s.push_str(&self.transpile_trace(source_file, source_map)?);
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_stmt(&mut self, statement: &'a Statement) -> TranspilerResult<String> {
         match statement {
-            Statement::Variable(var) => self.transpile_var(var),
-            _ => todo!()
+            Statement::Variable(var) => self.transpile_var(&var.value),
+            Statement::Constant(var) => self.transpile_const(&var.value),
+            Statement::While(while_loop) => self.transpile_while(&while_loop.value.0, &while_loop.value.1),
+            Statement::For(for_loop) => self.transpile_for(&for_loop.value.0, &for_loop.value.1, &for_loop.value.2),
+            Statement::Return(value) => match &value.value {
+                Some(value) => self.transpile_return(Some(&value.value)).at(value),
+                None => self.transpile_return(None),
+            },
+            Statement::If(expr) | Statement::Match(expr) | Statement::Expression(expr) | Statement::Tail(expr) => {
+                let mut s = self.transpile_expr_stmt(&expr.value).at(expr)?;
+                writeln!(s)?;
+                Ok(s)
+            },
+            Statement::Continue(keyword) if !self.ctx.in_loop() => Err(TranspilerErrorKind::InvalidBreak { keyword: "continue", span: Some(*keyword) }),
+            Statement::Continue(_) => Ok(String::from("continue\n")),
+            Statement::Break(keyword) if !self.ctx.in_loop() => Err(TranspilerErrorKind::InvalidBreak { keyword: "break", span: Some(*keyword) }),
+            Statement::Break(_) => Ok(String::from("break\n")),
+            Statement::Function(func) => self.transpile_func(&func.value),
+            Statement::Include(path) => self.transpile_include(&path.value),
+            Statement::Comment(text) if self.ctx.options.comments => Ok(comment(&text.value)),
+            Statement::Comment(_) => Ok(String::new()),
         }
     }
 
-    fn transpile_expr(&self, expression: &'a Expression) -> TranspilerResult<String> {
+    fn transpile_expr(&mut self, expression: &'a Expression) -> TranspilerResult<String> {
         match expression {
-            // Expression::Block(block) => self.transpile_block(block),
+            Expression::Block(block) => Ok(format!("\"$(\n{})\"", indented(&self.transpile_branch(block, true)?))),
             Expression::Literal(literal) => self.transpile_literal(literal),
-            // Expression::Arithmetic(arithmetic) => self.transpile_arithmetic(arithmetic),
-            _ => todo!()
+            Expression::Identifier(identifier) => Ok(format!("\"${}\"", self.ctx.variable_name(identifier))),
+            Expression::Group(expr) => self.transpile_expr(expr),
+            Expression::ShellCommand(cmd) => Ok(format!("\"$({})\"", self.transpile_command(cmd)?)),
+            Expression::InterpolatedString(parts) => Ok(format!("\"{}\"", self.transpile_interpolation(parts)?)),
+            Expression::FunctionCall(call) if self.ctx.types.infer(expression) == ValueType::Boolean => {
+                Ok(format!("$( {} && echo true || echo false )", self.transpile_call(&call.0, &call.1)?))
+            },
+            Expression::FunctionCall(call) => Ok(format!("\"$({})\"", self.transpile_call(&call.0, &call.1)?)),
+            Expression::Assignment(assignment) => self.transpile_assignment(&assignment.0, &assignment.1, &assignment.2),
+            Expression::If(if_expr) => Ok(format!("\"$({})\"", self.transpile_if(&if_expr.0, &if_expr.1, if_expr.2.as_ref(), true)?)),
+            Expression::Match(match_expr) => Ok(format!("\"$({})\"", self.transpile_match(&match_expr.0, &match_expr.1, true)?)),
+            Expression::Arithmetic(_) | Expression::Unary(_) if self.ctx.types.infer(expression) == ValueType::Integer => {
+                Ok(format!("$(( {} ))", self.transpile_arithmetic(expression)?))
+            },
+            Expression::Arithmetic(arithmetic) if arithmetic.1.value == ArithmeticOperator::Add => {
+                self.transpile_concatenation(&arithmetic.0.value, &arithmetic.2.value)
+            },
+            Expression::Logical(_) | Expression::Unary(_) => {
+                Ok(format!("$( {} && echo true || echo false )", self.transpile_condition(expression)?))
+            },
+            // only `+` has a meaning for strings
+            Expression::Arithmetic(_) => Err(TranspilerErrorKind::type_mismatch(ValueType::Integer, ValueType::String)),
+            Expression::Range(_) => Err(TranspilerErrorKind::unsupported("a range outside of a `for` loop")),
         }
     }
 }
 
+impl ShellLowering for BashTranspiler {
+    fn transpile_command(&mut self, command: &ShellCommand) -> TranspilerResult<String> {
+        let mut s = verbatim(&command.name);
+
+        for word in &command.args {
+            s.push(' ');
+
+            for part in word {
+                match part {
+                    // written in shell syntax, so they're kept as they are
+                    ShellWordPart::Literal(text) => s.push_str(&verbatim(text)),
+                    ShellWordPart::SingleQuoted(text) => write!(s, "'{}'", verbatim(text))?,
+                    ShellWordPart::DoubleQuoted(parts) => {
+                        s.push('"');
+
+                        for part in parts {
+                            match part {
+                                StringPart::Literal(text) => s.push_str(&verbatim(text)),
+                                StringPart::Expression(expr) => s.push_str(&self.transpile_embedded(&expr.value)?),
+                            }
+                        }
+
+                        s.push('"');
+                    },
+                    ShellWordPart::Interpolation(expr) => write!(s, "\"{}\"", self.transpile_embedded(&expr.value)?)?,
+                    ShellWordPart::UnquotedInterpolation(expr) => s.push_str(&self.transpile_embedded(&expr.value)?),
+                }
+            }
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_call(&mut self, name: &str, args: &[WithCursor<Expression>]) -> TranspilerResult<String> {
+        self.ctx.check_call(name, args)?;
+
+        let mut s = self.ctx.function_name(name);
+        let parameters = self.ctx.types.function(name).map(|func| func.parameters.clone()).unwrap_or_default();
+
+        for (index, arg) in args.iter().enumerate() {
+            let value_type = parameters.get(index).copied().unwrap_or_default();
+            write!(s, " {}", self.transpile_value(value_type, &arg.value).at(arg)?)?;
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_if(&mut self, condition: &WithCursor<Expression>, truthy: &WithCursor<Block>, else_block: Option<&Else>, as_value: bool) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "if {}; then", self.transpile_condition(&condition.value)?)?;
+        write!(s, "{}", indented(&self.transpile_branch(&truthy.value, as_value)?))?;
+
+        let mut else_block = else_block;
+        while let Some(block) = else_block {
+            // `else if` chains are flattened into `elif`
+            if let [Statement::If(expr) | Statement::Tail(expr)] = block.value.as_slice() {
+                if let Expression::If(if_expr) = &expr.value {
+                    writeln!(s, "elif {}; then", self.transpile_condition(&if_expr.0.value)?)?;
+                    write!(s, "{}", indented(&self.transpile_branch(&if_expr.1.value, as_value)?))?;
+                    else_block = if_expr.2.as_ref();
+                    continue;
+                }
+            }
+
+            writeln!(s, "else")?;
+            write!(s, "{}", indented(&self.transpile_branch(&block.value, as_value)?))?;
+            break;
+        }
+
+        write!(s, "fi")?;
+
+        Ok(s)
+    }
+
+    fn transpile_match(&mut self, value: &WithCursor<Expression>, arms: &MatchCase, as_value: bool) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "case {} in", self.transpile_expr(&value.value).at(value)?)?;
+
+        for arm in arms {
+            let patterns = arm.patterns.iter()
+                .map(|pattern| self.transpile_pattern(&pattern.value))
+                .collect::<TranspilerResult<Vec<_>>>()?;
+
+            let mut body = match &arm.value.value {
+                expr if as_value => self.transpile_output(expr)?,
+                Expression::Block(block) => self.transpile_branch(block, false)?,
+                expr => format!("{}\n", self.transpile_expr_stmt(expr)?),
+            };
+            body.push_str(";;\n");
+
+            // the patterns are nested in the `case`, and the body of an arm in its patterns
+            s.push_str(&indented(&format!("{})\n{}", patterns.join("|"), indented(&body))));
+        }
+
+        write!(s, "esac")?;
+
+        Ok(s)
+    }
+
+    fn transpile_condition(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Literal(literal) if matches!(literal.as_ref(), Literal::Boolean(_)) => self.transpile_literal(literal)?,
+            Expression::Group(expr) => self.transpile_condition(expr)?,
+            // the exit status of a command is its truthiness, as is the one of functions returning `Bool`
+            Expression::ShellCommand(_) => self.transpile_expr_stmt(expression)?,
+            Expression::FunctionCall(_) if matches!(self.ctx.types.infer(expression), ValueType::Boolean | ValueType::Unknown) => {
+                self.transpile_expr_stmt(expression)?
+            },
+            Expression::Unary(unary) if unary.0.value == UnaryOperator::Not => {
+                format!("! {}", self.transpile_condition_operand(&unary.1.value, None)?)
+            },
+            Expression::Logical(logical) => {
+                let (lhs, operator, rhs) = logical.as_ref();
+
+                match operator.value {
+                    LogicalOperator::And | LogicalOperator::Or => format!(
+                        "{} {} {}",
+                        self.transpile_condition_operand(&lhs.value, Some(operator.value))?,
+                        if operator.value == LogicalOperator::And { "&&" } else { "||" },
+                        self.transpile_condition_operand(&rhs.value, Some(operator.value))?,
+                    ),
+                    _ => self.transpile_comparison(&lhs.value, operator.value, &rhs.value)?,
+                }
+            },
+            _ => match self.ctx.types.infer(expression) {
+                ValueType::Integer => {
+                    let expression = self.transpile_arithmetic(expression)?;
+                    self.arithmetic_test(&expression)
+                },
+                ValueType::String if self.posix => format!("[ -n {} ]", self.transpile_expr(expression)?),
+                ValueType::String => format!("[[ -n {} ]]", self.transpile_expr(expression)?),
+                ValueType::Boolean | ValueType::Unknown if self.posix => format!("[ {} = true ]", self.transpile_expr(expression)?),
+                ValueType::Boolean | ValueType::Unknown => format!("[[ {} == true ]]", self.transpile_expr(expression)?),
+            },
+        })
+    }
+
+    fn transpile_block_expr(&mut self, block: &Block) -> TranspilerResult<String> {
+        Ok(format!("{{\n{}}}", indented(&self.scoped(ScopeKind::Block, |this| this.transpile_block(block))?)))
+    }
+
+    fn transpile_echo(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        // `echo` of sh expands backslash escapes in its arguments
+        if self.posix {
+            return Ok(format!("printf '%s\\n' {}\n", self.transpile_expr(expression)?));
+        }
+
+        Ok(format!("echo {}\n", self.transpile_expr(expression)?))
+    }
+
+    /// `:` does nothing with its arguments, which are still expanded. It also
+    /// keeps the block the value is in from being empty, which is an error in sh
+    fn transpile_discarded(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        if has_effects(expression) {
+            return Ok(format!(": {}", self.transpile_expr(expression)?));
+        }
+
+        Ok(String::from(":"))
+    }
+
+    fn group_condition(&self, condition: &str) -> String {
+        format!("{{ {condition}; }}")
+    }
+}
+
 impl BashTranspiler {
-    fn transpile_var(&self, var: &Variable) -> TranspilerResult<String> {
+    pub fn create(ctx: TranspilerContext) -> Self {
+        Self {
+            ctx,
+            ..Default::default()
+        }
+    }
+
+    pub fn posix(ctx: TranspilerContext) -> Self {
+        Self {
+            ctx,
+            posix: true,
+        }
+    }
+
+    /// Turns on tracing with `set -x`, with `PS4` tagging every traced command with the line of the source
+    /// it comes from, and reports the line of a failing command. `$LINENO` is turned into a line of the
+    /// source through a table of the lines of the program, which starts right after the prelude
+    fn transpile_trace(&self, source_file: &SourceFile, source_map: &SourceMap) -> TranspilerResult<String> {
+        if self.posix {
+            return Err(TranspilerErrorKind::unsupported("tracing the lines of the source"));
+        }
+
+        let lines = (1..=source_map.lines())
+            .map(|line| source_map.lookup(line).map(|span| span.start.line().to_string()).unwrap_or(String::from("?")))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let location = "${__tsh_source}:${__tsh_lines[LINENO - __tsh_offset]:-?}";
+        let mut s = String::new();
+
+        writeln!(s, "__tsh_source={}", verbatim(&PosixQuoting::quote(source_file.get_path())))?;
+        writeln!(s, "__tsh_lines=([1]={lines})")?;
+        writeln!(s, "PS4='+ {location}: '")?;
+        // `-E` passes the trap on to functions and subshells
+        writeln!(s, "set -E")?;
+        writeln!(s, "trap 'echo \"{location}: failed with status $?\" >&2' ERR")?;
+        // the program starts after `set -x` and what separates it from the prelude
+        writeln!(s, "__tsh_offset=$(( LINENO + {} ))", 1 + self.ctx.options.prelude_separator().lines().count())?;
+        writeln!(s, "set -x")?;
+
+        Ok(s)
+    }
+
+    /// Wraps an arithmetic expression into a test, `(( ))` is an extension of bash
+    fn arithmetic_test(&self, expression: &str) -> String {
+        if self.posix {
+            format!("[ $(( {expression} )) -ne 0 ]")
+        } else {
+            format!("(( {expression} ))")
+        }
+    }
+
+    /// Variables declared in a function are local to it
+    fn transpile_var(&mut self, var: &Variable) -> TranspilerResult<String> {
         let mut s = String::new();
 
-        write!(s, "{}", var.name)?;
-        
+        if self.ctx.function().is_some() && self.capabilities().local_variables {
+            write!(s, "local ")?;
+        }
+
+        self.ctx.declare(&var.name)?;
+        write!(s, "{}", self.ctx.variable_name(&var.name))?;
+
+        self.ctx.check_var(var)?;
+
         if let Some(value) = &var.value {
-            let value = self.transpile_expr(&value.value)?;
+            let value = self.transpile_value(self.ctx.types.variable(&var.name), &value.value).at(value)?;
 
             write!(s, "={}", value)?;
         }
@@ -45,13 +358,354 @@ impl BashTranspiler {
         Ok(s)
     }
 
-    fn transpile_literal(&self, literal: &Literal) -> TranspilerResult<String> {
-        Ok(match literal {
-            Literal::String(string) => string.to_string(),
-            Literal::Boolean(bool) => bool.to_string(),
-            Literal::Integer(int) => int.to_string()
+    /// `readonly` always marks the global variable, so in a function a constant
+    /// is declared with `local -r`, or as a plain local where that doesn't exist
+    fn transpile_const(&mut self, var: &Variable) -> TranspilerResult<String> {
+        if self.ctx.function().is_none() {
+            return Ok(format!("readonly {}", self.transpile_var(var)?));
+        }
+
+        let var = self.transpile_var(var)?;
+
+        match var.strip_prefix("local ") {
+            Some(var) if !self.posix => Ok(format!("local -r {var}")),
+            _ => Ok(var),
+        }
+    }
+
+    fn transpile_include(&mut self, path: &str) -> TranspilerResult<String> {
+        let path = include_path(path, "sh");
+
+        let command = if self.posix { "." } else { "source" };
+
+        Ok(format!("{command} {}\n", verbatim(&PosixQuoting::quote(&path))))
+    }
+
+    fn transpile_func(&mut self, func: &Function) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        s.push_str(&usage_header(&self.ctx.options, func));
+        writeln!(s, "{}() {{", self.ctx.function_name(&func.name))?;
+
+        let body = self.scoped(ScopeKind::Function(func.name.clone()), |this| {
+            let mut s = String::new();
+
+            // arguments are bound to named locals, so the body never deals with `$1..$n` itself
+            for (index, param) in func.parameters.iter().flatten().enumerate() {
+                let position = index + 1;
+                let name = this.ctx.variable_name(&param.name);
+                this.ctx.declare(&param.name)?;
+
+                match &param.value {
+                    Some(default) => {
+                        writeln!(s, "local {name}={}", this.transpile_value(this.ctx.types.variable(&param.name), &default.value).at(default)?)?;
+                        writeln!(s, "if {}; then {name}=\"${position}\"; fi", this.arithmetic_test(&format!("$# >= {position}")))?;
+                    },
+                    None => writeln!(s, "local {name}=\"${position}\"")?,
+                }
+            }
+
+            s.push_str(&this.transpile_func_body(&func.body.value)?);
+            Ok(s)
+        })?;
+
+        write!(s, "{}", indented(&body))?;
+        writeln!(s, "}}")?;
+
+        Ok(s)
+    }
+
+    fn transpile_while(&mut self, condition: &WithCursor<Expression>, block: &WithCursor<Block>) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "while {}; do", self.transpile_condition(&condition.value).at(condition)?)?;
+        write!(s, "{}", indented(&self.scoped(ScopeKind::Loop, |this| this.transpile_block(&block.value))?))?;
+        writeln!(s, "done")?;
+
+        Ok(s)
+    }
+
+    fn transpile_for(&mut self, var: &Variable, iterable: &WithCursor<Expression>, block: &WithCursor<Block>) -> TranspilerResult<String> {
+        let mut s = String::new();
+        let name = &self.ctx.variable_name(&var.name);
+
+        match unwrap_group(&iterable.value) {
+            Expression::Range(range) if self.posix => {
+                let (start, end, inclusive) = range.as_ref();
+                let comparison = if *inclusive { "<=" } else { "<" };
+
+                // the counter is stepped in the condition, so `continue` steps it as well
+                let end = format!("{name} {comparison} {}", self.transpile_arithmetic(&end.value).at(end)?);
+
+                writeln!(s, "{name}=$(( {} - 1 ))", self.transpile_arithmetic(&start.value).at(start)?)?;
+                writeln!(s, "while {name}=$(( {name} + 1 )); {}; do", self.arithmetic_test(&end))?;
+            },
+            Expression::Range(range) => {
+                let (start, end, inclusive) = range.as_ref();
+                let comparison = if *inclusive { "<=" } else { "<" };
+
+                writeln!(
+                    s,
+                    "for (( {name} = {}; {name} {comparison} {}; {name}++ )); do",
+                    self.transpile_arithmetic(&start.value).at(start)?,
+                    self.transpile_arithmetic(&end.value).at(end)?,
+                )?;
+            },
+            // every line of the output is an iteration, without being split into words
+            Expression::ShellCommand(_) => writeln!(s, "while IFS= read -r {name}; do")?,
+            expr => writeln!(s, "for {name} in {}; do", self.transpile_iterable(expr).at(iterable)?)?,
+        }
+
+        let mut body = String::new();
+
+        // a glob matching nothing is left as it is, where the other shells have no paths to iterate
+        if matches!(unwrap_group(&iterable.value), Expression::Literal(literal) if matches!(literal.as_ref(), Literal::String(pattern) if is_glob(pattern))) {
+            writeln!(body, "[ -e \"${name}\" ] || continue")?;
+        }
+
+        body.push_str(&self.scoped(ScopeKind::Loop, |this| this.transpile_block(&block.value))?);
+        write!(s, "{}", indented(&body))?;
+
+        match unwrap_group(&iterable.value) {
+            // a here-document keeps the loop out of a subshell, where process substitution isn't available.
+            // It always holds a line, so the loop is skipped when the command has no output
+            Expression::ShellCommand(cmd) if self.posix => {
+                writeln!(s, "done <<EOF{}$__tsh_output{}EOF", verbatim("\n"), verbatim("\n"))?;
+
+                let mut output = format!("__tsh_output=$({})", self.transpile_command(cmd)?);
+
+                // a failing command doesn't stop the script, as it doesn't in a process substitution
+                if self.ctx.options.strict {
+                    output.push_str(" || true");
+                }
+
+                return Ok(format!("{output}\nif [ -n \"$__tsh_output\" ]; then\n{}fi\n", indented(&s)));
+            },
+            Expression::ShellCommand(cmd) => writeln!(s, "done < <({})", self.transpile_command(cmd)?)?,
+            _ => writeln!(s, "done")?,
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_iterable(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        match expression {
+            // a variable is iterated as an array, a plain value is an array of one
+            Expression::Identifier(_) if !self.capabilities().arrays => Err(TranspilerErrorKind::unsupported("iterating over a variable")),
+            Expression::Identifier(identifier) => Ok(format!("\"${{{}[@]}}\"", self.ctx.variable_name(identifier))),
+            Expression::Literal(literal) => match literal.as_ref() {
+                Literal::String(pattern) if is_glob(pattern) => Ok(verbatim(&PosixQuoting::quote_glob(pattern))),
+                literal => self.transpile_literal(literal),
+            },
+            _ => self.transpile_expr(expression),
+        }
+    }
+
+    /// Numbers are compared arithmetically, anything else is compared as a string
+    fn transpile_comparison(&mut self, lhs: &Expression, operator: LogicalOperator, rhs: &Expression) -> TranspilerResult<String> {
+        let lhs_type = self.ctx.types.infer(lhs);
+        let rhs_type = self.ctx.types.infer(rhs);
+
+        if (lhs_type, rhs_type) == (ValueType::Integer, ValueType::Integer) {
+            let operator = match operator {
+                LogicalOperator::Equal => "==",
+                LogicalOperator::NotEqual => "!=",
+                LogicalOperator::LesserThan => "<",
+                LogicalOperator::LesserEqualThan => "<=",
+                LogicalOperator::GreaterThan => ">",
+                LogicalOperator::GreaterEqualThan => ">=",
+                LogicalOperator::And | LogicalOperator::Or => unreachable!(),
+            };
+
+            let expression = format!("{} {operator} {}", self.transpile_arithmetic(lhs)?, self.transpile_arithmetic(rhs)?);
+            return Ok(self.arithmetic_test(&expression));
+        }
+
+        // a value that isn't known to be a number is compared with `[`, which only reads numbers,
+        // where `[[` and `(( ))` would evaluate it as arithmetic
+        if matches!((lhs_type, rhs_type), (ValueType::Integer, ValueType::Unknown) | (ValueType::Unknown, ValueType::Integer)) {
+            let operator = match operator {
+                LogicalOperator::Equal => "-eq",
+                LogicalOperator::NotEqual => "-ne",
+                LogicalOperator::LesserThan => "-lt",
+                LogicalOperator::LesserEqualThan => "-le",
+                LogicalOperator::GreaterThan => "-gt",
+                LogicalOperator::GreaterEqualThan => "-ge",
+                LogicalOperator::And | LogicalOperator::Or => unreachable!(),
+            };
+
+            return Ok(format!("[ {} {operator} {} ]", self.transpile_expr(lhs)?, self.transpile_expr(rhs)?));
+        }
+
+        let lhs = self.transpile_expr(lhs)?;
+        let rhs = self.transpile_expr(rhs)?;
+
+        if self.posix {
+            return match operator {
+                LogicalOperator::Equal => Ok(format!("[ {lhs} = {rhs} ]")),
+                LogicalOperator::NotEqual => Ok(format!("[ {lhs} != {rhs} ]")),
+                _ => Err(TranspilerErrorKind::unsupported("ordering strings")),
+            };
+        }
+
+        // `[[` has no `<=` and `>=` for strings, so they are written as the negation of the opposite
+        Ok(match operator {
+            LogicalOperator::Equal => format!("[[ {lhs} == {rhs} ]]"),
+            LogicalOperator::NotEqual => format!("[[ {lhs} != {rhs} ]]"),
+            LogicalOperator::LesserThan => format!("[[ {lhs} < {rhs} ]]"),
+            LogicalOperator::LesserEqualThan => format!("! [[ {lhs} > {rhs} ]]"),
+            LogicalOperator::GreaterThan => format!("[[ {lhs} > {rhs} ]]"),
+            LogicalOperator::GreaterEqualThan => format!("! [[ {lhs} < {rhs} ]]"),
+            LogicalOperator::And | LogicalOperator::Or => unreachable!(),
+        })
+    }
+
+    fn transpile_pattern(&mut self, pattern: &MatchPattern) -> TranspilerResult<String> {
+        match pattern {
+            MatchPattern::Literal(literal) => self.transpile_literal(literal),
+            MatchPattern::Wildcard => Ok(String::from("*")),
+        }
+    }
+
+    /// Transpiles the inside of a double quoted string
+    fn transpile_interpolation(&mut self, parts: &InterpolatedString) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        for part in parts {
+            match part {
+                StringPart::Literal(text) => s.push_str(&verbatim(&PosixQuoting::escape_double_quoted(text))),
+                StringPart::Expression(expr) => s.push_str(&self.transpile_embedded(&expr.value)?),
+            }
+        }
+
+        Ok(s)
+    }
+
+    /// Transpiles an expression embedded in a double quoted string
+    fn transpile_embedded(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Identifier(identifier) => format!("${{{}}}", self.ctx.variable_name(identifier)),
+            Expression::Literal(literal) => verbatim(&PosixQuoting::escape_double_quoted(&literal_text(literal))),
+            Expression::Group(expr) => self.transpile_embedded(expr)?,
+            Expression::InterpolatedString(parts) => self.transpile_interpolation(parts)?,
+            Expression::ShellCommand(cmd) => format!("$({})", self.transpile_command(cmd)?),
+            Expression::FunctionCall(call) if self.ctx.types.infer(expression) != ValueType::Boolean => {
+                format!("$({})", self.transpile_call(&call.0, &call.1)?)
+            },
+            Expression::Block(block) => format!("$(\n{})", indented(&self.transpile_branch(block, true)?)),
+            Expression::If(if_expr) => format!("$({})", self.transpile_if(&if_expr.0, &if_expr.1, if_expr.2.as_ref(), true)?),
+            Expression::Match(match_expr) => format!("$({})", self.transpile_match(&match_expr.0, &match_expr.1, true)?),
+            Expression::Arithmetic(arithmetic) if arithmetic.1.value == ArithmeticOperator::Add
+                && self.ctx.types.infer(expression) != ValueType::Integer => {
+                // already inside the quotes, so the operands are placed next to each other without quotes of their own
+                format!("{}{}", self.transpile_embedded(&arithmetic.0.value)?, self.transpile_embedded(&arithmetic.2.value)?)
+            },
+            _ => self.transpile_expr(expression)?,
+        })
+    }
+
+    fn transpile_assignment(&mut self, target: &WithCursor<Expression>, operator: &WithCursor<AssignmentOperator>, value: &WithCursor<Expression>) -> TranspilerResult<String> {
+        let Expression::Identifier(identifier) = &target.value else {
+            return Err(TranspilerErrorKind::unsupported("assigning to anything but a variable").at(target));
+        };
+        let name = self.ctx.variable_name(identifier);
+        let value_type = self.ctx.types.variable(identifier);
+
+        let operator = match operator.value {
+            AssignmentOperator::Assign => return Ok(format!("{name}={}", self.transpile_value(value_type, &value.value).at(value)?)),
+            AssignmentOperator::PlusAssign => "+=",
+            AssignmentOperator::MinusAssign => "-=",
+            AssignmentOperator::MultiplyAssign => "*=",
+            AssignmentOperator::DivideAssign => "/=",
+        };
+
+        let value = self.transpile_arithmetic(&value.value).at(value)?;
+
+        // `(( x += 1 ))` reads `x` by name, so it's only written for a variable known to hold a number
+        if self.posix || value_type != ValueType::Integer {
+            let operator = &operator[..1];
+            return Ok(format!("{name}=$(( {} {operator} ({value}) ))", self.transpile_arithmetic(&target.value)?));
+        }
+
+        // `(( ))` fails when the result is 0, which would stop a script in strict mode
+        if self.ctx.options.strict {
+            return Ok(format!("(( {name} {operator} {value} )) || true"));
+        }
+
+        Ok(format!("(( {name} {operator} {value} ))"))
+    }
+
+    /// Transpiles an expression to be used inside of an arithmetic context, such as `$(( ... ))`.
+    /// Variables holding numbers are referenced by name as bash resolves them itself
+    fn transpile_arithmetic(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        let numeric = self.ctx.types.infer(expression) == ValueType::Integer;
+
+        Ok(match expression {
+            Expression::Literal(literal) => match literal.as_ref() {
+                Literal::Integer(int) => int.to_string(),
+                Literal::Boolean(bool) => (*bool as u8).to_string(),
+                Literal::String(_) => return Err(TranspilerErrorKind::type_mismatch(ValueType::Integer, ValueType::String)),
+            },
+            Expression::Identifier(identifier) if numeric => self.ctx.variable_name(identifier),
+            Expression::Group(expr) => format!("({})", self.transpile_arithmetic(expr)?),
+            Expression::FunctionCall(_) if numeric => format!("$({})", self.transpile_expr_stmt(expression)?),
+            Expression::Unary(unary) => {
+                let operand = self.transpile_arithmetic(&unary.1.value).at(&unary.1)?;
+
+                match unary.0.value {
+                    // `--` and `!!` would otherwise be read as a single operator
+                    UnaryOperator::Negative if operand.starts_with('-') => format!("-({operand})"),
+                    UnaryOperator::Negative => format!("-{operand}"),
+                    UnaryOperator::Not if operand.starts_with('!') => format!("!({operand})"),
+                    UnaryOperator::Not => format!("!{operand}"),
+                }
+            },
+            Expression::Arithmetic(arithmetic) => {
+                let (lhs, operator, rhs) = arithmetic.as_ref();
+
+                let operator = match operator.value {
+                    ArithmeticOperator::Add => "+",
+                    ArithmeticOperator::Subtract => "-",
+                    ArithmeticOperator::Multiply => "*",
+                    ArithmeticOperator::Divide => "/",
+                };
+
+                format!(
+                    "{} {operator} {}",
+                    self.transpile_arithmetic(&lhs.value).at(lhs)?,
+                    self.transpile_arithmetic(&rhs.value).at(rhs)?,
+                )
+            },
+            _ if numeric => self.transpile_expr(expression)?,
+            _ => self.transpile_integer_guard(expression)?,
+        })
+    }
+
+    /// Transpiles a value that isn't known to be a number for an arithmetic context. bash evaluates a value there
+    /// as arithmetic in turn, which runs the commands in an array subscript, so a value with anything but digits
+    /// and `-` is emptied, and the empty parentheses fail with a syntax error
+    fn transpile_integer_guard(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Identifier(identifier) => format!("(${{{}##{NOT_INTEGER}}})", self.ctx.variable_name(identifier)),
+            _ => format!("($(__tsh_value={}; echo \"${{__tsh_value##{NOT_INTEGER}}}\"))", self.transpile_expr(expression)?),
         })
     }
 
-    // fn transpile_arithmetic(&self, arithmetic: )
-}
\ No newline at end of file
+    /// Transpiles a value stored in a variable of the given type. Variables holding numbers are
+    /// read by name in arithmetic, so a value that isn't known to be one is checked first
+    fn transpile_value(&mut self, value_type: ValueType, expression: &Expression) -> TranspilerResult<String> {
+        match value_type {
+            ValueType::Integer if self.ctx.types.infer(expression) != ValueType::Integer => {
+                Ok(format!("$(( {} ))", self.transpile_arithmetic(expression)?))
+            },
+            _ => self.transpile_expr(expression),
+        }
+    }
+
+    fn transpile_literal(&mut self, literal: &Literal) -> TranspilerResult<String> {
+        Ok(match literal {
+            Literal::String(string) => verbatim(&PosixQuoting::quote(string)),
+            _ => literal_text(literal),
+        })
+    }
+}
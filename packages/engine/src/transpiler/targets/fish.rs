@@ -0,0 +1,592 @@
+use std::fmt::Write;
+use crate::{cursor::WithCursor, error::SourceFile, parser::ast::{ArithmeticOperator, AssignmentOperator, Block, Else, Expression, Function, InterpolatedString, Literal, LogicalOperator, MatchCase, MatchPattern, ShellCommand, ShellWord, ShellWordPart, Statement, StringPart, UnaryOperator, Variable}, transpiler::{error::{Locate, TranspilerErrorKind, TranspilerResult}, quote::{FishQuoting, Quoting}, types::ValueType, source_map::SourceMap, writer::{indented, verbatim}, Capabilities, ScopeKind, TranspilerContext, TranspilerImpl}};
+
+use super::{comment, has_effects, include_path, is_glob, literal_text, prelude_header, usage_header, unescape, unwrap_group, ShellLowering};
+
+/// Transpiles to fish 3.4 or newer, which is the first version with `$(...)` substitutions
+/// that can be quoted. Values are passed around the same way as in bash, as every variable
+/// of fish is a list, a value is kept as a list of one by quoting it wherever it's used
+#[derive(Default)]
+pub struct FishTranspiler {
+    ctx: TranspilerContext,
+}
+
+impl<'a> TranspilerImpl<'a> for FishTranspiler {
+    fn ctx(&self) -> &TranspilerContext {
+        &self.ctx
+    }
+
+    fn ctx_mut(&mut self) -> &mut TranspilerContext {
+        &mut self.ctx
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            arrays: true,
+            floats: true,
+            local_variables: true,
+        }
+    }
+
+    /// fish never splits values into words, so there is no `IFS` to set
+    fn transpile_prelude(&mut self, source_file: &SourceFile, _source_map: &SourceMap) -> TranspilerResult<String> {
+        if self.ctx.options.strict {
+            return Err(TranspilerErrorKind::unsupported("stopping at the first failing command"));
+        }
+
+        if self.ctx.options.debug {
+            return Err(TranspilerErrorKind::unsupported("tracing the lines of the source"));
+        }
+
+        Ok(prelude_header(&self.ctx.options, source_file))
+    }
+
+    fn transpile_stmt(&mut self, statement: &'a Statement) -> TranspilerResult<String> {
+        match statement {
+            Statement::Variable(var) | Statement::Constant(var) => self.transpile_var(&var.value),
+            Statement::While(while_loop) => self.transpile_while(&while_loop.value.0, &while_loop.value.1),
+            Statement::For(for_loop) => self.transpile_for(&for_loop.value.0, &for_loop.value.1, &for_loop.value.2),
+            Statement::Return(value) => match &value.value {
+                Some(value) => self.transpile_return(Some(&value.value)).at(value),
+                None => self.transpile_return(None),
+            },
+            Statement::If(expr) | Statement::Match(expr) | Statement::Expression(expr) | Statement::Tail(expr) => {
+                let s = self.transpile_expr_stmt(&expr.value).at(expr)?;
+                Ok(line(s))
+            },
+            Statement::Continue(keyword) if !self.ctx.in_loop() => Err(TranspilerErrorKind::InvalidBreak { keyword: "continue", span: Some(*keyword) }),
+            Statement::Continue(_) => Ok(String::from("continue\n")),
+            Statement::Break(keyword) if !self.ctx.in_loop() => Err(TranspilerErrorKind::InvalidBreak { keyword: "break", span: Some(*keyword) }),
+            Statement::Break(_) => Ok(String::from("break\n")),
+            Statement::Function(func) => self.transpile_func(&func.value),
+            Statement::Include(path) => self.transpile_include(&path.value),
+            Statement::Comment(text) if self.ctx.options.comments => Ok(comment(&text.value)),
+            Statement::Comment(_) => Ok(String::new()),
+        }
+    }
+
+    fn transpile_expr(&mut self, expression: &'a Expression) -> TranspilerResult<String> {
+        match expression {
+            Expression::Literal(literal) => self.transpile_literal(literal),
+            Expression::Group(expr) => self.transpile_expr(expr),
+            Expression::InterpolatedString(parts) => Ok(format!("\"{}\"", self.transpile_interpolation(parts)?)),
+            Expression::Assignment(assignment) => self.transpile_assignment(&assignment.0, &assignment.1, &assignment.2),
+            Expression::Arithmetic(_) | Expression::Unary(_) if self.ctx.types.infer(expression) == ValueType::Integer => {
+                self.transpile_math(expression)
+            },
+            Expression::Arithmetic(arithmetic) if arithmetic.1.value == ArithmeticOperator::Add => {
+                self.transpile_concatenation(&arithmetic.0.value, &arithmetic.2.value)
+            },
+            // quoted, a variable or a substitution is never split into several values
+            Expression::Block(_)
+            | Expression::Identifier(_)
+            | Expression::ShellCommand(_)
+            | Expression::FunctionCall(_)
+            | Expression::If(_)
+            | Expression::Match(_)
+            | Expression::Logical(_)
+            | Expression::Unary(_) => Ok(format!("\"{}\"", self.transpile_embedded(expression)?)),
+            Expression::Arithmetic(_) => Err(TranspilerErrorKind::type_mismatch(ValueType::Integer, ValueType::String)),
+            Expression::Range(_) => Err(TranspilerErrorKind::unsupported("a range outside of a `for` loop")),
+        }
+    }
+}
+
+impl ShellLowering for FishTranspiler {
+    fn transpile_command(&mut self, command: &ShellCommand) -> TranspilerResult<String> {
+        let mut s = verbatim(&command.name);
+
+        for word in &command.args {
+            write!(s, " {}", self.transpile_word(word)?)?;
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_call(&mut self, name: &str, args: &[WithCursor<Expression>]) -> TranspilerResult<String> {
+        self.ctx.check_call(name, args)?;
+
+        let mut s = self.ctx.function_name(name);
+
+        for arg in args {
+            write!(s, " {}", self.transpile_expr(&arg.value).at(arg)?)?;
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_if(&mut self, condition: &WithCursor<Expression>, truthy: &WithCursor<Block>, else_block: Option<&Else>, as_value: bool) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "if {}", self.transpile_condition(&condition.value)?)?;
+        write!(s, "{}", indented(&self.transpile_branch(&truthy.value, as_value)?))?;
+
+        let mut else_block = else_block;
+        while let Some(block) = else_block {
+            if let [Statement::If(expr) | Statement::Tail(expr)] = block.value.as_slice() {
+                if let Expression::If(if_expr) = &expr.value {
+                    writeln!(s, "else if {}", self.transpile_condition(&if_expr.0.value)?)?;
+                    write!(s, "{}", indented(&self.transpile_branch(&if_expr.1.value, as_value)?))?;
+                    else_block = if_expr.2.as_ref();
+                    continue;
+                }
+            }
+
+            writeln!(s, "else")?;
+            write!(s, "{}", indented(&self.transpile_branch(&block.value, as_value)?))?;
+            break;
+        }
+
+        write!(s, "end")?;
+
+        Ok(s)
+    }
+
+    fn transpile_match(&mut self, value: &WithCursor<Expression>, arms: &MatchCase, as_value: bool) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "switch {}", self.transpile_expr(&value.value).at(value)?)?;
+
+        for arm in arms {
+            let patterns = arm.patterns.iter()
+                .map(|pattern| self.transpile_pattern(&pattern.value))
+                .collect::<TranspilerResult<Vec<_>>>()?;
+
+            let body = match &arm.value.value {
+                expr if as_value => self.transpile_output(expr)?,
+                Expression::Block(block) => self.transpile_branch(block, false)?,
+                expr => line(self.transpile_expr_stmt(expr)?),
+            };
+
+            // the cases are nested in the `switch`, and the body of a case in the case
+            s.push_str(&indented(&format!("case {}\n{}", patterns.join(" "), indented(&body))));
+        }
+
+        write!(s, "end")?;
+
+        Ok(s)
+    }
+
+    fn transpile_condition(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Literal(literal) if matches!(literal.as_ref(), Literal::Boolean(_)) => literal_text(literal),
+            Expression::Group(expr) => self.transpile_condition(expr)?,
+            Expression::ShellCommand(_) => self.transpile_expr_stmt(expression)?,
+            Expression::FunctionCall(_) if matches!(self.ctx.types.infer(expression), ValueType::Boolean | ValueType::Unknown) => {
+                self.transpile_expr_stmt(expression)?
+            },
+            Expression::Unary(unary) if unary.0.value == UnaryOperator::Not => {
+                format!("not {}", self.transpile_condition_operand(&unary.1.value, None)?)
+            },
+            Expression::Logical(logical) => {
+                let (lhs, operator, rhs) = logical.as_ref();
+
+                match operator.value {
+                    LogicalOperator::And | LogicalOperator::Or => format!(
+                        "{} {} {}",
+                        self.transpile_condition_operand(&lhs.value, Some(operator.value))?,
+                        if operator.value == LogicalOperator::And { "&&" } else { "||" },
+                        self.transpile_condition_operand(&rhs.value, Some(operator.value))?,
+                    ),
+                    _ => self.transpile_comparison(&lhs.value, operator.value, &rhs.value)?,
+                }
+            },
+            _ => match self.ctx.types.infer(expression) {
+                ValueType::Integer => format!("test {} -ne 0", self.transpile_expr(expression)?),
+                ValueType::String => format!("test -n {}", self.transpile_expr(expression)?),
+                ValueType::Boolean | ValueType::Unknown => format!("test {} = true", self.transpile_expr(expression)?),
+            },
+        })
+    }
+
+    fn transpile_block_expr(&mut self, block: &Block) -> TranspilerResult<String> {
+        Ok(format!("begin\n{}end", indented(&self.transpile_branch(block, false)?)))
+    }
+
+    fn transpile_echo(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        // `echo` would read a value starting with a dash as its options
+        Ok(format!("printf '%s\\n' {}\n", self.transpile_expr(expression)?))
+    }
+
+    /// fish has no `:`, but `true` does nothing with its arguments either, which are still expanded.
+    /// A value without anything to run is left out, as fish allows empty blocks
+    fn transpile_discarded(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        if has_effects(expression) {
+            return Ok(format!("true {}", self.transpile_expr(expression)?));
+        }
+
+        Ok(String::new())
+    }
+
+    fn group_condition(&self, condition: &str) -> String {
+        format!("begin; {condition}; end")
+    }
+}
+
+impl FishTranspiler {
+    pub fn create(ctx: TranspilerContext) -> Self {
+        Self {
+            ctx
+        }
+    }
+
+    /// fish has no read-only variables, so constants are set like any other variable.
+    /// In a function, a plain `set` would change a global variable of the same name
+    fn transpile_var(&mut self, var: &Variable) -> TranspilerResult<String> {
+        let mut s = String::from("set ");
+
+        if self.ctx.function().is_some() {
+            s.push_str("-l ");
+        }
+
+        self.ctx.declare(&var.name)?;
+        s.push_str(&self.ctx.variable_name(&var.name));
+
+        self.ctx.check_var(var)?;
+
+        if let Some(value) = &var.value {
+            write!(s, " {}", self.transpile_expr(&value.value).at(value)?)?;
+        }
+
+        writeln!(s)?;
+
+        Ok(s)
+    }
+
+    fn transpile_include(&mut self, path: &str) -> TranspilerResult<String> {
+        let path = include_path(path, "fish");
+
+        Ok(format!("source {}\n", verbatim(&FishQuoting::quote(&path))))
+    }
+
+    fn transpile_func(&mut self, func: &Function) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        s.push_str(&usage_header(&self.ctx.options, func));
+        write!(s, "function {}", self.ctx.function_name(&func.name))?;
+
+        if let Some(parameters) = func.parameters.as_ref().filter(|parameters| !parameters.is_empty()) {
+            let names = parameters.iter().map(|param| self.ctx.variable_name(&param.name)).collect::<Vec<_>>();
+            write!(s, " --argument-names {}", names.join(" "))?;
+        }
+
+        writeln!(s)?;
+
+        let body = self.scoped(ScopeKind::Function(func.name.clone()), |this| {
+            let mut s = String::new();
+
+            for (index, param) in func.parameters.iter().flatten().enumerate() {
+                this.ctx.declare(&param.name)?;
+
+                if let Some(default) = &param.value {
+                    writeln!(s, "set -q argv[{}]; or set {} {}", index + 1, this.ctx.variable_name(&param.name), this.transpile_expr(&default.value).at(default)?)?;
+                }
+            }
+
+            s.push_str(&this.transpile_func_body(&func.body.value)?);
+            Ok(s)
+        })?;
+
+        write!(s, "{}", indented(&body))?;
+        writeln!(s, "end")?;
+
+        Ok(s)
+    }
+
+    fn transpile_while(&mut self, condition: &WithCursor<Expression>, block: &WithCursor<Block>) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "while {}", self.transpile_condition(&condition.value).at(condition)?)?;
+        write!(s, "{}", indented(&self.scoped(ScopeKind::Loop, |this| this.transpile_block(&block.value))?))?;
+        writeln!(s, "end")?;
+
+        Ok(s)
+    }
+
+    fn transpile_for(&mut self, var: &Variable, iterable: &WithCursor<Expression>, block: &WithCursor<Block>) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "for {} in {}", self.ctx.variable_name(&var.name), self.transpile_iterable(unwrap_group(&iterable.value)).at(iterable)?)?;
+        write!(s, "{}", indented(&self.scoped(ScopeKind::Loop, |this| this.transpile_block(&block.value))?))?;
+        writeln!(s, "end")?;
+
+        Ok(s)
+    }
+
+    fn transpile_iterable(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        match expression {
+            Expression::Range(range) => {
+                let (start, end, inclusive) = range.as_ref();
+
+                let end = match &end.value {
+                    value if *inclusive => self.transpile_expr(value).at(end)?,
+                    Expression::Literal(literal) => match literal.as_ref() {
+                        Literal::Integer(int) => (int - 1).to_string(),
+                        _ => return Err(TranspilerErrorKind::type_mismatch(ValueType::Integer, self.ctx.types.infer(&end.value)).at(end)),
+                    },
+                    value => format!("(math -s0 \"{} - 1\")", self.transpile_math_operand(value).at(end)?),
+                };
+
+                Ok(format!("(seq {} {end})", self.transpile_expr(&start.value).at(start)?))
+            },
+            // unquoted, the output is split into lines and every line is an iteration
+            Expression::ShellCommand(cmd) => Ok(format!("({})", self.transpile_command(cmd)?)),
+            // every variable is a list already
+            Expression::Identifier(identifier) => Ok(format!("${}", self.ctx.variable_name(identifier))),
+            Expression::Literal(literal) => match literal.as_ref() {
+                Literal::String(pattern) if is_glob(pattern) => {
+                    if pattern.contains(['?', '[']) {
+                        return Err(TranspilerErrorKind::unsupported("`?` and `[...]` in a glob"));
+                    }
+
+                    Ok(verbatim(&FishQuoting::quote_glob(pattern)))
+                },
+                literal => self.transpile_literal(literal),
+            },
+            _ => self.transpile_expr(expression),
+        }
+    }
+
+    /// Numbers are compared arithmetically, anything else is compared as a string.
+    /// `test` of fish can't order strings, so only their equality can be checked
+    fn transpile_comparison(&mut self, lhs: &Expression, operator: LogicalOperator, rhs: &Expression) -> TranspilerResult<String> {
+        let numeric = matches!(
+            (self.ctx.types.infer(lhs), self.ctx.types.infer(rhs)),
+            (ValueType::Integer, ValueType::Integer | ValueType::Unknown) | (ValueType::Unknown, ValueType::Integer)
+        );
+
+        let operator = match operator {
+            LogicalOperator::Equal if numeric => "-eq",
+            LogicalOperator::NotEqual if numeric => "-ne",
+            LogicalOperator::LesserThan if numeric => "-lt",
+            LogicalOperator::LesserEqualThan if numeric => "-le",
+            LogicalOperator::GreaterThan if numeric => "-gt",
+            LogicalOperator::GreaterEqualThan if numeric => "-ge",
+            LogicalOperator::Equal => "=",
+            LogicalOperator::NotEqual => "!=",
+            LogicalOperator::And | LogicalOperator::Or => unreachable!(),
+            _ => return Err(TranspilerErrorKind::unsupported("ordering strings")),
+        };
+
+        Ok(format!("test {} {operator} {}", self.transpile_expr(lhs)?, self.transpile_expr(rhs)?))
+    }
+
+    /// `case` matches its patterns as globs even when they're quoted
+    fn transpile_pattern(&mut self, pattern: &MatchPattern) -> TranspilerResult<String> {
+        match pattern {
+            MatchPattern::Literal(literal) if literal_text(literal).contains(['*', '?']) => {
+                Err(TranspilerErrorKind::unsupported("`*` and `?` in a match pattern"))
+            },
+            MatchPattern::Literal(literal) => self.transpile_literal(literal),
+            MatchPattern::Wildcard => Ok(String::from("'*'")),
+        }
+    }
+
+    /// Transpiles a shell word to a single argument, as it was written in shell syntax
+    fn transpile_word(&mut self, word: &ShellWord) -> TranspilerResult<String> {
+        Ok(match word.as_slice() {
+            [ShellWordPart::Literal(text)] => {
+                let unescaped = unescape(text, |_| true);
+
+                if unescaped.chars().all(|char| char.is_ascii_alphanumeric() || matches!(char, '-' | '_' | '.' | '/' | ':' | '=' | '+' | '%' | ',' | '@')) {
+                    unescaped
+                } else if text.contains('*') && !text.contains('\\') {
+                    verbatim(&FishQuoting::quote_glob(text))
+                } else {
+                    verbatim(&FishQuoting::quote(&unescaped))
+                }
+            },
+            [ShellWordPart::SingleQuoted(text)] => verbatim(&FishQuoting::quote(text)),
+            [ShellWordPart::Interpolation(expr)] => self.transpile_expr(&expr.value)?,
+            // split on spaces into separate arguments, as an unquoted variable is in bash
+            [ShellWordPart::UnquotedInterpolation(expr)] => format!("(string split -n ' ' -- {})", self.transpile_expr(&expr.value)?),
+            parts => {
+                let mut s = String::from('"');
+                let mut text = String::new();
+
+                for part in parts {
+                    match part {
+                        ShellWordPart::Literal(literal) => text.push_str(&unescape(literal, |_| true)),
+                        ShellWordPart::SingleQuoted(literal) => text.push_str(literal),
+                        ShellWordPart::DoubleQuoted(parts) => {
+                            for part in parts {
+                                match part {
+                                    StringPart::Literal(literal) => text.push_str(&unescape(literal, |char| matches!(char, '$' | '`' | '"' | '\\'))),
+                                    StringPart::Expression(expr) => {
+                                        self.push_embedded(&mut s, &std::mem::take(&mut text), &expr.value)?;
+                                    },
+                                }
+                            }
+                        },
+                        ShellWordPart::Interpolation(expr) | ShellWordPart::UnquotedInterpolation(expr) => {
+                            self.push_embedded(&mut s, &std::mem::take(&mut text), &expr.value)?;
+                        },
+                    }
+                }
+
+                push_text(&mut s, &text);
+                s.push('"');
+                s
+            },
+        })
+    }
+
+    /// Transpiles the inside of a double quoted string
+    fn transpile_interpolation(&mut self, parts: &InterpolatedString) -> TranspilerResult<String> {
+        let mut s = String::new();
+        let mut text = String::new();
+
+        for part in parts {
+            match part {
+                StringPart::Literal(literal) => text.push_str(literal),
+                StringPart::Expression(expr) => self.push_embedded(&mut s, &std::mem::take(&mut text), &expr.value)?,
+            }
+        }
+
+        push_text(&mut s, &text);
+
+        Ok(s)
+    }
+
+    /// Pushes the text before an embedded expression and the expression itself to the inside of a double quoted string
+    fn push_embedded(&mut self, s: &mut String, text: &str, expression: &Expression) -> TranspilerResult<()> {
+        push_text(s, text);
+        s.push_str(&self.transpile_embedded(expression)?);
+
+        Ok(())
+    }
+
+    /// Transpiles an expression embedded in a double quoted string
+    fn transpile_embedded(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Identifier(identifier) => format!("${}", self.ctx.variable_name(identifier)),
+            Expression::Literal(literal) => verbatim(&FishQuoting::escape_double_quoted(&literal_text(literal))),
+            Expression::Group(expr) => self.transpile_embedded(expr)?,
+            Expression::InterpolatedString(parts) => self.transpile_interpolation(parts)?,
+            Expression::Block(block) => format!("$(\n{})", indented(&self.transpile_branch(block, true)?)),
+            Expression::ShellCommand(cmd) => format!("$({})", self.transpile_command(cmd)?),
+            Expression::FunctionCall(call) if self.ctx.types.infer(expression) != ValueType::Boolean => {
+                format!("$({})", self.transpile_call(&call.0, &call.1)?)
+            },
+            Expression::If(if_expr) => format!("$({})", self.transpile_if(&if_expr.0, &if_expr.1, if_expr.2.as_ref(), true)?),
+            Expression::Match(match_expr) => format!("$({})", self.transpile_match(&match_expr.0, &match_expr.1, true)?),
+            Expression::Arithmetic(_) | Expression::Unary(_) if self.ctx.types.infer(expression) == ValueType::Integer => {
+                format!("${}", self.transpile_math(expression)?)
+            },
+            Expression::FunctionCall(_) | Expression::Logical(_) | Expression::Unary(_) => {
+                format!("$({} && echo true || echo false)", self.transpile_condition(expression)?)
+            },
+            _ => format!("$(printf '%s' {})", self.transpile_expr(expression)?),
+        })
+    }
+
+    fn transpile_assignment(&mut self, target: &WithCursor<Expression>, operator: &WithCursor<AssignmentOperator>, value: &WithCursor<Expression>) -> TranspilerResult<String> {
+        let Expression::Identifier(identifier) = &target.value else {
+            return Err(TranspilerErrorKind::unsupported("assigning to anything but a variable").at(target));
+        };
+        let name = self.ctx.variable_name(identifier);
+
+        let operator = match operator.value {
+            AssignmentOperator::Assign => return Ok(format!("set {name} {}", self.transpile_expr(&value.value).at(value)?)),
+            AssignmentOperator::PlusAssign if self.ctx.types.variable(identifier) == ValueType::String => {
+                return Ok(format!("set {name} \"${name}\"{}", self.transpile_expr(&value.value)?));
+            },
+            AssignmentOperator::PlusAssign => "+",
+            AssignmentOperator::MinusAssign => "-",
+            AssignmentOperator::MultiplyAssign => "*",
+            AssignmentOperator::DivideAssign => "/",
+        };
+
+        let value = match &value.value {
+            Expression::Arithmetic(_) => format!("({})", self.transpile_math_operand(&value.value)?),
+            value => self.transpile_math_operand(value)?,
+        };
+
+        Ok(format!("set {name} (math -s0 \"${name} {operator} {value}\")"))
+    }
+
+    /// Integers are calculated with `math`, with a scale of 0 so the fraction of a division is dropped
+    fn transpile_math(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        let expression = self.transpile_math_operand(expression)?;
+
+        // `math` would read a leading dash as one of its options
+        let separator = if expression.starts_with('-') { "-- " } else { "" };
+
+        Ok(format!("(math -s0 {separator}\"{expression}\")"))
+    }
+
+    /// Transpiles an expression to the inside of the double quoted expression given to `math`
+    fn transpile_math_operand(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Literal(literal) => match literal.as_ref() {
+                Literal::Integer(int) => int.to_string(),
+                Literal::Boolean(bool) => (*bool as u8).to_string(),
+                Literal::String(_) => return Err(TranspilerErrorKind::type_mismatch(ValueType::Integer, ValueType::String)),
+            },
+            Expression::Identifier(identifier) => format!("${}", self.ctx.variable_name(identifier)),
+            Expression::Group(expr) => format!("({})", self.transpile_math_operand(expr)?),
+            Expression::FunctionCall(_) | Expression::ShellCommand(_) => format!("$({})", self.transpile_expr_stmt(expression)?),
+            Expression::Unary(unary) => {
+                let operand = &unary.1.value;
+
+                match unary.0.value {
+                    UnaryOperator::Negative => {
+                        let operand = self.transpile_math_operand(operand)?;
+
+                        if operand.starts_with('-') {
+                            format!("-({operand})")
+                        } else {
+                            format!("-{operand}")
+                        }
+                    },
+                    // `math` has no logical operators
+                    UnaryOperator::Not => format!("$(test {} -eq 0 && echo 1 || echo 0)", self.transpile_expr(operand)?),
+                }
+            },
+            Expression::Arithmetic(arithmetic) => {
+                let (lhs, operator, rhs) = arithmetic.as_ref();
+
+                let operator = match operator.value {
+                    ArithmeticOperator::Add => "+",
+                    ArithmeticOperator::Subtract => "-",
+                    ArithmeticOperator::Multiply => "*",
+                    ArithmeticOperator::Divide => "/",
+                };
+
+                format!(
+                    "{} {operator} {}",
+                    self.transpile_math_operand(&lhs.value).at(lhs)?,
+                    self.transpile_math_operand(&rhs.value).at(rhs)?,
+                )
+            },
+            _ => self.transpile_embedded(expression)?,
+        })
+    }
+
+    fn transpile_literal(&mut self, literal: &Literal) -> TranspilerResult<String> {
+        Ok(match literal {
+            Literal::String(string) => verbatim(&FishQuoting::quote(string)),
+            _ => literal_text(literal),
+        })
+    }
+}
+
+/// Pushes literal text to the inside of a double quoted string. A variable name ends at the first character
+/// that can't be part of it, so when the text would continue the name, the quotes are closed and reopened
+fn push_text(s: &mut String, text: &str) {
+    let continues_name = text.starts_with(|char: char| char.is_ascii_alphanumeric() || matches!(char, '_' | '['));
+
+    if continues_name && s.rsplit_once('$').is_some_and(|(_, name)| name.chars().all(|char| char.is_ascii_alphanumeric() || char == '_')) {
+        s.push_str("\"\"");
+    }
+
+    s.push_str(&verbatim(&FishQuoting::escape_double_quoted(text)));
+}
+
+/// Ends the code of a statement with a newline, unless there's no code, which happens when a value is left out
+fn line(code: String) -> String {
+    match code.is_empty() {
+        true => code,
+        false => format!("{code}\n"),
+    }
+}
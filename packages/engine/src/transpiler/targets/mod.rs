@@ -1 +1,255 @@
-pub(super) mod bash;
\ No newline at end of file
+use std::fmt::Write;
+use crate::{cursor::WithCursor, error::SourceFile, parser::ast::{split_trailing_comments, Block, Else, Expression, Function, Literal, LogicalOperator, MatchCase, ShellCommand, Statement, StringPart}};
+
+use super::{error::{Locate, TranspilerResult}, types::ValueType, writer::verbatim, ScopeKind, TranspilerImpl, TranspilerOptions};
+
+pub(super) mod bash;
+pub(super) mod fish;
+pub(super) mod powershell;
+
+/// Lowering shared by bash and fish, which pass values around the same way. Values are written to the
+/// output, and booleans are exit statuses. Targets give the syntax, the structure is the same for both
+trait ShellLowering: for<'a> TranspilerImpl<'a> + Sized {
+    fn transpile_command(&mut self, command: &ShellCommand) -> TranspilerResult<String>;
+    fn transpile_call(&mut self, name: &str, args: &[WithCursor<Expression>]) -> TranspilerResult<String>;
+    fn transpile_if(&mut self, condition: &WithCursor<Expression>, truthy: &WithCursor<Block>, else_block: Option<&Else>, as_value: bool) -> TranspilerResult<String>;
+    fn transpile_match(&mut self, value: &WithCursor<Expression>, arms: &MatchCase, as_value: bool) -> TranspilerResult<String>;
+    /// Lowers an expression to a command, which succeeds when the expression is true
+    fn transpile_condition(&mut self, expression: &Expression) -> TranspilerResult<String>;
+
+    /// Lowers a block run as a statement of its own
+    fn transpile_block_expr(&mut self, block: &Block) -> TranspilerResult<String>;
+    /// Writes a value that isn't the output of a command to the output, as a line
+    fn transpile_echo(&mut self, expression: &Expression) -> TranspilerResult<String>;
+    /// Lowers a value nothing uses, which runs the commands in it but never the value itself
+    fn transpile_discarded(&mut self, expression: &Expression) -> TranspilerResult<String>;
+    /// Groups a list of conditions, so it's read as one
+    fn group_condition(&self, condition: &str) -> String;
+
+    fn transpile_block(&mut self, block: &[Statement]) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        for statement in block {
+            s.push_str(&self.ctx_mut().mark(statement));
+            s.push_str(&self.transpile_stmt(statement).at(&statement.span())?);
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_expr_stmt(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        match expression {
+            Expression::ShellCommand(cmd) => self.transpile_command(cmd),
+            Expression::FunctionCall(call) => self.transpile_call(&call.0, &call.1),
+            Expression::Group(expr) => self.transpile_expr_stmt(expr),
+            Expression::Block(block) => self.transpile_block_expr(block),
+            Expression::If(if_expr) => self.transpile_if(&if_expr.0, &if_expr.1, if_expr.2.as_ref(), false),
+            Expression::Match(match_expr) => self.transpile_match(&match_expr.0, &match_expr.1, false),
+            Expression::Assignment(_) => self.transpile_expr(expression),
+            // e.g. the tail of a block whose value isn't used, which would otherwise be run as a command
+            _ => self.transpile_discarded(expression),
+        }
+    }
+
+    /// Lowers an expression to commands writing its value to the output
+    fn transpile_output(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            // commands write their output themselves
+            Expression::ShellCommand(_) => format!("{}\n", self.transpile_expr_stmt(expression)?),
+            Expression::FunctionCall(_) if self.ctx().types.infer(expression) != ValueType::Boolean => {
+                format!("{}\n", self.transpile_expr_stmt(expression)?)
+            },
+            Expression::Group(expr) => self.transpile_output(expr)?,
+            Expression::Block(block) => self.transpile_branch(block, true)?,
+            Expression::If(if_expr) => format!("{}\n", self.transpile_if(&if_expr.0, &if_expr.1, if_expr.2.as_ref(), true)?),
+            Expression::Match(match_expr) => format!("{}\n", self.transpile_match(&match_expr.0, &match_expr.1, true)?),
+            _ => self.transpile_echo(expression)?,
+        })
+    }
+
+    /// Lowers a block whose value is the value of its tail expression
+    fn transpile_block_output(&mut self, block: &Block) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        for statement in block {
+            s.push_str(&self.ctx_mut().mark(statement));
+            match statement {
+                Statement::Tail(expr) => s.push_str(&self.transpile_output(&expr.value)?),
+                statement => s.push_str(&self.transpile_stmt(statement)?),
+            }
+        }
+
+        Ok(s)
+    }
+
+    /// Lowers the block of a branch, which gives back its value when the branch is used as one
+    fn transpile_branch(&mut self, block: &Block, as_value: bool) -> TranspilerResult<String> {
+        self.scoped(ScopeKind::Block, |this| if as_value {
+            this.transpile_block_output(block)
+        } else {
+            this.transpile_block(block)
+        })
+    }
+
+    fn transpile_func_body(&mut self, block: &Block) -> TranspilerResult<String> {
+        let (statements, comments) = split_trailing_comments(block);
+
+        match statements.split_last() {
+            Some((Statement::Tail(expr), rest)) if self.ctx().return_type() == ValueType::Boolean => {
+                let mut s = self.transpile_block(rest)?;
+                s.push_str(&self.transpile_return(Some(&expr.value))?);
+                s.push_str(&self.transpile_block(comments)?);
+                Ok(s)
+            },
+            _ => self.transpile_block_output(block),
+        }
+    }
+
+    fn transpile_return(&mut self, value: Option<&Expression>) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        match value {
+            // booleans are returned as the exit status
+            Some(expr) if self.ctx().return_type() == ValueType::Boolean => match expr {
+                Expression::Literal(literal) if matches!(literal.as_ref(), Literal::Boolean(true)) => writeln!(s, "return 0")?,
+                Expression::Literal(literal) if matches!(literal.as_ref(), Literal::Boolean(false)) => writeln!(s, "return 1")?,
+                expr => writeln!(s, "{} && return 0\nreturn 1", self.transpile_condition(expr)?)?,
+            },
+            Some(expr) => {
+                write!(s, "{}", self.transpile_output(expr)?)?;
+                writeln!(s, "return")?;
+            },
+            None => writeln!(s, "return")?,
+        }
+
+        Ok(s)
+    }
+
+    /// Strings are concatenated by placing them next to each other
+    fn transpile_concatenation(&mut self, lhs: &Expression, rhs: &Expression) -> TranspilerResult<String> {
+        Ok(format!("{}{}", self.transpile_expr(lhs)?, self.transpile_expr(rhs)?))
+    }
+
+    /// `&&` and `||` share the same precedence, so operands joined with
+    /// a different operator are grouped to keep the precedence of the source
+    fn transpile_condition_operand(&mut self, expression: &Expression, parent: Option<LogicalOperator>) -> TranspilerResult<String> {
+        let inner = unwrap_group(expression);
+        let condition = self.transpile_condition(inner)?;
+
+        Ok(match inner {
+            Expression::Logical(logical) if matches!(logical.1.value, LogicalOperator::And | LogicalOperator::Or) && parent != Some(logical.1.value) => {
+                self.group_condition(&condition)
+            },
+            _ => condition,
+        })
+    }
+}
+
+/// The text of a literal as it would be printed
+fn literal_text(literal: &Literal) -> String {
+    match literal {
+        Literal::String(string) => string.to_string(),
+        Literal::Boolean(bool) => bool.to_string(),
+        Literal::Integer(int) => int.to_string()
+    }
+}
+
+/// The shebang and the comment marking a script as generated, which every shell writes the same way
+fn prelude_header(options: &TranspilerOptions, source_file: &SourceFile) -> String {
+    let mut s = String::new();
+
+    if let Some(shebang) = &options.shebang {
+        s.push_str(&format!("#!{}\n", verbatim(shebang)));
+    }
+
+    if options.header {
+        s.push_str(&format!("# generated from {}, edit that file instead of this one\n", verbatim(source_file.get_path())));
+    }
+
+    s
+}
+
+/// The path of an included script, which is expected to be transpiled alongside this one
+/// to a file with the extension of the target
+fn include_path(path: &str, extension: &str) -> String {
+    match path.strip_suffix(".tsh") {
+        Some(stem) => format!("{stem}.{extension}"),
+        None => path.to_string(),
+    }
+}
+
+/// A comment of the source written as a `#` comment for each of its lines, which every shell reads the same way
+fn comment(text: &str) -> String {
+    text.lines().map(|line| format!("#{}\n", verbatim(line.trim_end()))).collect()
+}
+
+/// How to call a documented function, written above it followed by its doc comments,
+/// e.g. `# Usage: greet <name> [times]`
+fn usage_header(options: &TranspilerOptions, func: &Function) -> String {
+    if !options.comments || func.docs.is_empty() {
+        return String::new();
+    }
+
+    let mut s = format!("# Usage: {}", func.name);
+
+    for param in func.parameters.iter().flatten() {
+        // parameters with a default can be left out
+        match param.value {
+            Some(_) => s.push_str(&format!(" [{}]", param.name)),
+            None => s.push_str(&format!(" <{}>", param.name)),
+        }
+    }
+
+    s.push_str("\n#\n");
+
+    for line in &func.docs {
+        s.push_str(&comment(line));
+    }
+
+    s
+}
+
+/// Steps into groups, which have no meaning outside of an expression
+fn unwrap_group(expression: &Expression) -> &Expression {
+    match expression {
+        Expression::Group(expr) => unwrap_group(expr),
+        expr => expr,
+    }
+}
+
+/// Whether lowering a value runs anything, such as a command or a function, which has to run even when the value isn't used
+fn has_effects(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal(_) | Expression::Identifier(_) => false,
+        Expression::Group(expr) => has_effects(expr),
+        Expression::Unary(unary) => has_effects(&unary.1.value),
+        Expression::Arithmetic(arithmetic) => has_effects(&arithmetic.0.value) || has_effects(&arithmetic.2.value),
+        Expression::Logical(logical) => has_effects(&logical.0.value) || has_effects(&logical.2.value),
+        Expression::Range(range) => has_effects(&range.0.value) || has_effects(&range.1.value),
+        Expression::InterpolatedString(parts) => parts.iter().any(|part| matches!(part, StringPart::Expression(expr) if has_effects(&expr.value))),
+        _ => true,
+    }
+}
+
+/// Whether a string is meant to be expanded into the paths it matches, e.g. `"src/*.rs"`
+fn is_glob(text: &str) -> bool {
+    text.contains(['*', '?', '['])
+}
+
+/// Removes the backslashes of shell syntax in front of the characters they escape
+fn unescape(text: &str, escapes: impl Fn(char) -> bool) -> String {
+    let mut s = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match chars.peek() {
+            Some(&next) if char == '\\' && escapes(next) => {
+                s.push(next);
+                chars.next();
+            },
+            _ => s.push(char),
+        }
+    }
+
+    s
+}
@@ -0,0 +1,597 @@
+use std::fmt::Write;
+use crate::{cursor::WithCursor, error::SourceFile, parser::ast::{ArithmeticOperator, AssignmentOperator, Block, Else, Expression, Function, InterpolatedString, Literal, LogicalOperator, MatchCase, MatchPattern, ShellCommand, ShellWord, ShellWordPart, Statement, StringPart, UnaryOperator, Variable}, transpiler::{error::{Locate, TranspilerErrorKind, TranspilerResult}, quote::{PowerShellQuoting, Quoting}, types::ValueType, source_map::SourceMap, writer::{indented, verbatim}, Capabilities, ScopeKind, TranspilerContext, TranspilerImpl}};
+
+use super::{comment, include_path, is_glob, literal_text, prelude_header, usage_header, unescape, unwrap_group};
+
+/// PowerShell passes values around as objects, so unlike the shells, every expression
+/// statement writes its value to the output of the function it's in. Blocks, `if` and
+/// `switch` give back their tail expression that way without any help
+#[derive(Default)]
+pub struct PowerShellTranspiler {
+    ctx: TranspilerContext,
+}
+
+impl<'a> TranspilerImpl<'a> for PowerShellTranspiler {
+    fn ctx(&self) -> &TranspilerContext {
+        &self.ctx
+    }
+
+    fn ctx_mut(&mut self) -> &mut TranspilerContext {
+        &mut self.ctx
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            arrays: true,
+            floats: true,
+            local_variables: true,
+        }
+    }
+
+    /// Values are never split into words, so there is no `IFS` to set. Native commands only stop
+    /// the script on failure from PowerShell 7.3 on, older versions ignore the preference
+    fn transpile_prelude(&mut self, source_file: &SourceFile, _source_map: &SourceMap) -> TranspilerResult<String> {
+        if self.ctx.options.debug {
+            return Err(TranspilerErrorKind::unsupported("tracing the lines of the source"));
+        }
+
+        let mut s = prelude_header(&self.ctx.options, source_file);
+
+        if self.ctx.options.strict {
+            writeln!(s, "Set-StrictMode -Version Latest")?;
+            writeln!(s, "$ErrorActionPreference = 'Stop'")?;
+            writeln!(s, "$PSNativeCommandUseErrorActionPreference = $true")?;
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_stmt(&mut self, statement: &'a Statement) -> TranspilerResult<String> {
+        match statement {
+            Statement::Variable(var) => self.transpile_var(&var.value),
+            Statement::Constant(var) => self.transpile_const(&var.value),
+            Statement::While(while_loop) => self.transpile_while(&while_loop.value.0, &while_loop.value.1),
+            Statement::For(for_loop) => self.transpile_for(&for_loop.value.0, &for_loop.value.1, &for_loop.value.2),
+            Statement::Return(value) => match &value.value {
+                Some(value) => self.transpile_return(Some(&value.value)).at(value),
+                None => self.transpile_return(None),
+            },
+            Statement::If(expr) | Statement::Match(expr) | Statement::Expression(expr) | Statement::Tail(expr) => {
+                let mut s = self.transpile_expr_stmt(&expr.value).at(expr)?;
+                writeln!(s)?;
+                Ok(s)
+            },
+            Statement::Continue(keyword) if !self.ctx.in_loop() => Err(TranspilerErrorKind::InvalidBreak { keyword: "continue", span: Some(*keyword) }),
+            Statement::Continue(_) => Ok(String::from("continue\n")),
+            Statement::Break(keyword) if !self.ctx.in_loop() => Err(TranspilerErrorKind::InvalidBreak { keyword: "break", span: Some(*keyword) }),
+            Statement::Break(_) => Ok(String::from("break\n")),
+            Statement::Function(func) => self.transpile_func(&func.value),
+            Statement::Include(path) => self.transpile_include(&path.value),
+            Statement::Comment(text) if self.ctx.options.comments => Ok(comment(&text.value)),
+            Statement::Comment(_) => Ok(String::new()),
+        }
+    }
+
+    fn transpile_expr(&mut self, expression: &'a Expression) -> TranspilerResult<String> {
+        match expression {
+            Expression::Block(block) => Ok(format!("$(\n{})", indented(&self.transpile_scope(ScopeKind::Block, block)?))),
+            Expression::Literal(literal) => self.transpile_literal(literal),
+            Expression::Identifier(identifier) => Ok(format!("${}", self.ctx.variable_name(identifier))),
+            Expression::Group(expr) => Ok(format!("({})", self.transpile_expr(expr)?)),
+            // the lines of the output are joined back together, as `$(...)` does in the shells
+            Expression::ShellCommand(cmd) => Ok(format!("(({}) -join \"`n\")", self.transpile_command(cmd)?)),
+            Expression::InterpolatedString(parts) => Ok(format!("\"{}\"", self.transpile_interpolation(parts)?)),
+            Expression::FunctionCall(call) => Ok(format!("({})", self.transpile_call(&call.0, &call.1)?)),
+            Expression::Assignment(assignment) => self.transpile_assignment(&assignment.0, &assignment.1, &assignment.2),
+            Expression::If(if_expr) => Ok(format!("$({})", self.transpile_if(&if_expr.0, &if_expr.1, if_expr.2.as_ref())?)),
+            Expression::Match(match_expr) => Ok(format!("$({})", self.transpile_match(&match_expr.0, &match_expr.1)?)),
+            Expression::Arithmetic(arithmetic) => self.transpile_arithmetic(&arithmetic.0, arithmetic.1.value, &arithmetic.2),
+            Expression::Unary(unary) => self.transpile_unary(unary.0.value, &unary.1),
+            Expression::Logical(_) => self.transpile_condition(expression),
+            Expression::Range(_) => Err(TranspilerErrorKind::unsupported("a range outside of a `for` loop")),
+        }
+    }
+}
+
+impl PowerShellTranspiler {
+    pub fn create(ctx: TranspilerContext) -> Self {
+        Self {
+            ctx
+        }
+    }
+
+    fn transpile_block(&mut self, block: &[Statement]) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        for statement in block {
+            s.push_str(&self.ctx.mark(statement));
+            s.push_str(&self.transpile_stmt(statement).at(&statement.span())?);
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_scope(&mut self, kind: ScopeKind, block: &[Statement]) -> TranspilerResult<String> {
+        self.scoped(kind, |this| this.transpile_block(block))
+    }
+
+    /// Transpiles an expression in statement position.
+    /// Commands and calls are run directly instead of being captured
+    fn transpile_expr_stmt(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        match expression {
+            Expression::ShellCommand(cmd) => self.transpile_command(cmd),
+            Expression::FunctionCall(call) => self.transpile_call(&call.0, &call.1),
+            Expression::Group(expr) => self.transpile_expr_stmt(expr),
+            // dot sourcing keeps the block in the current scope, like a `{ ... }` group does in bash
+            Expression::Block(block) => Ok(format!(". {{\n{}}}", indented(&self.transpile_scope(ScopeKind::Block, block)?))),
+            Expression::If(if_expr) => self.transpile_if(&if_expr.0, &if_expr.1, if_expr.2.as_ref()),
+            Expression::Match(match_expr) => self.transpile_match(&match_expr.0, &match_expr.1),
+            _ => self.transpile_expr(expression),
+        }
+    }
+
+    fn transpile_var(&mut self, var: &Variable) -> TranspilerResult<String> {
+        self.ctx.check_var(var)?;
+        self.ctx.declare(&var.name)?;
+        let name = self.ctx.variable_name(&var.name);
+
+        match &var.value {
+            Some(value) => Ok(format!("${name} = {}\n", self.transpile_expr(&value.value).at(value)?)),
+            None => Ok(format!("${name} = $null\n")),
+        }
+    }
+
+    fn transpile_const(&mut self, var: &Variable) -> TranspilerResult<String> {
+        self.ctx.check_var(var)?;
+
+        let value = match &var.value {
+            Some(value) => self.transpile_arg(&value.value).at(value)?,
+            None => String::from("$null"),
+        };
+
+        Ok(format!("Set-Variable -Name {} -Value {value} -Option ReadOnly\n", self.ctx.variable_name(&var.name)))
+    }
+
+    fn transpile_include(&mut self, path: &str) -> TranspilerResult<String> {
+        let path = include_path(path, "ps1");
+
+        Ok(format!(". {}\n", verbatim(&PowerShellQuoting::quote(&path))))
+    }
+
+    fn transpile_func(&mut self, func: &Function) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        s.push_str(&usage_header(&self.ctx.options, func));
+        writeln!(s, "function {} {{", self.ctx.function_name(&func.name))?;
+
+        let mut body = String::new();
+
+        if let Some(parameters) = &func.parameters {
+            let parameters = parameters.iter()
+                .map(|param| {
+                    let mut s = String::new();
+
+                    if let Some(strict_type) = &param.strict_type {
+                        s.push_str(type_name(ValueType::from_name(strict_type)));
+                    }
+
+                    write!(s, "${}", self.ctx.variable_name(&param.name))?;
+
+                    if let Some(default) = &param.value {
+                        write!(s, " = {}", self.transpile_expr(&default.value).at(default)?)?;
+                    }
+
+                    Ok(s)
+                })
+                .collect::<TranspilerResult<Vec<_>>>()?;
+
+            writeln!(body, "param({})", parameters.join(", "))?;
+        }
+
+        body.push_str(&self.transpile_scope(ScopeKind::Function(func.name.clone()), &func.body.value)?);
+        write!(s, "{}", indented(&body))?;
+        writeln!(s, "}}")?;
+
+        Ok(s)
+    }
+
+    fn transpile_return(&mut self, value: Option<&Expression>) -> TranspilerResult<String> {
+        match value {
+            Some(expr) => Ok(format!("return {}\n", self.transpile_expr(expr)?)),
+            None => Ok(String::from("return\n")),
+        }
+    }
+
+    fn transpile_while(&mut self, condition: &WithCursor<Expression>, block: &WithCursor<Block>) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "while ({}) {{", self.transpile_condition(&condition.value).at(condition)?)?;
+        write!(s, "{}", indented(&self.transpile_scope(ScopeKind::Loop, &block.value)?))?;
+        writeln!(s, "}}")?;
+
+        Ok(s)
+    }
+
+    fn transpile_for(&mut self, var: &Variable, iterable: &WithCursor<Expression>, block: &WithCursor<Block>) -> TranspilerResult<String> {
+        let mut s = String::new();
+        let name = &self.ctx.variable_name(&var.name);
+
+        match unwrap_group(&iterable.value) {
+            Expression::Range(range) => {
+                let (start, end, inclusive) = range.as_ref();
+                let comparison = if *inclusive { "-le" } else { "-lt" };
+
+                writeln!(
+                    s,
+                    "for (${name} = {}; ${name} {comparison} {}; ${name}++) {{",
+                    self.transpile_expr(&start.value).at(start)?,
+                    self.transpile_expr(&end.value).at(end)?,
+                )?;
+            },
+            expr => writeln!(s, "foreach (${name} in {}) {{", self.transpile_iterable(expr).at(iterable)?)?,
+        }
+
+        write!(s, "{}", indented(&self.transpile_scope(ScopeKind::Loop, &block.value)?))?;
+        writeln!(s, "}}")?;
+
+        Ok(s)
+    }
+
+    fn transpile_iterable(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        match expression {
+            // native commands already give back their output as an array of lines
+            Expression::ShellCommand(cmd) => Ok(format!("({})", self.transpile_command(cmd)?)),
+            Expression::Literal(literal) => match literal.as_ref() {
+                Literal::String(pattern) if is_glob(pattern) => Ok(format!(
+                    "(Resolve-Path -Path {} -Relative -ErrorAction Ignore)",
+                    verbatim(&PowerShellQuoting::quote_glob(pattern)),
+                )),
+                literal => self.transpile_literal(literal),
+            },
+            _ => self.transpile_expr(expression),
+        }
+    }
+
+    fn transpile_if(&mut self, condition: &WithCursor<Expression>, truthy: &WithCursor<Block>, else_block: Option<&Else>) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        writeln!(s, "if ({}) {{", self.transpile_condition(&condition.value)?)?;
+        write!(s, "{}", indented(&self.transpile_scope(ScopeKind::Block, &truthy.value)?))?;
+
+        let mut else_block = else_block;
+        while let Some(block) = else_block {
+            if let [Statement::If(expr) | Statement::Tail(expr)] = block.value.as_slice() {
+                if let Expression::If(if_expr) = &expr.value {
+                    writeln!(s, "}} elseif ({}) {{", self.transpile_condition(&if_expr.0.value)?)?;
+                    write!(s, "{}", indented(&self.transpile_scope(ScopeKind::Block, &if_expr.1.value)?))?;
+                    else_block = if_expr.2.as_ref();
+                    continue;
+                }
+            }
+
+            writeln!(s, "}} else {{")?;
+            write!(s, "{}", indented(&self.transpile_scope(ScopeKind::Block, &block.value)?))?;
+            break;
+        }
+
+        write!(s, "}}")?;
+
+        Ok(s)
+    }
+
+    /// Values are truthy the same way in PowerShell, only commands need their exit code checked
+    fn transpile_condition(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Group(expr) => format!("({})", self.transpile_condition(expr)?),
+            Expression::ShellCommand(cmd) => format!("$({} | Out-Host; $LASTEXITCODE -eq 0)", self.transpile_command(cmd)?),
+            Expression::Unary(unary) if unary.0.value == UnaryOperator::Not => {
+                format!("-not {}", self.transpile_condition_operand(&unary.1.value, None)?)
+            },
+            Expression::Logical(logical) => {
+                let (lhs, operator, rhs) = logical.as_ref();
+
+                match operator.value {
+                    LogicalOperator::And | LogicalOperator::Or => format!(
+                        "{} {} {}",
+                        self.transpile_condition_operand(&lhs.value, Some(operator.value))?,
+                        if operator.value == LogicalOperator::And { "-and" } else { "-or" },
+                        self.transpile_condition_operand(&rhs.value, Some(operator.value))?,
+                    ),
+                    _ => self.transpile_comparison(&lhs.value, operator.value, &rhs.value)?,
+                }
+            },
+            _ => self.transpile_expr(expression)?,
+        })
+    }
+
+    /// `-and` and `-or` share the same precedence, so operands joined with
+    /// a different operator are grouped to keep the precedence of the source
+    fn transpile_condition_operand(&mut self, expression: &Expression, parent: Option<LogicalOperator>) -> TranspilerResult<String> {
+        let inner = unwrap_group(expression);
+        let condition = self.transpile_condition(inner)?;
+
+        Ok(match inner {
+            Expression::Logical(logical) if matches!(logical.1.value, LogicalOperator::And | LogicalOperator::Or) && parent != Some(logical.1.value) => {
+                format!("({condition})")
+            },
+            _ => condition,
+        })
+    }
+
+    /// Strings are compared case sensitively, like they are everywhere else
+    fn transpile_comparison(&mut self, lhs: &Expression, operator: LogicalOperator, rhs: &Expression) -> TranspilerResult<String> {
+        let numeric = matches!(
+            (self.ctx.types.infer(lhs), self.ctx.types.infer(rhs)),
+            (ValueType::Integer, ValueType::Integer | ValueType::Unknown) | (ValueType::Unknown, ValueType::Integer)
+        );
+
+        let operator = match operator {
+            LogicalOperator::Equal => "eq",
+            LogicalOperator::NotEqual => "ne",
+            LogicalOperator::LesserThan => "lt",
+            LogicalOperator::LesserEqualThan => "le",
+            LogicalOperator::GreaterThan => "gt",
+            LogicalOperator::GreaterEqualThan => "ge",
+            LogicalOperator::And | LogicalOperator::Or => unreachable!(),
+        };
+
+        Ok(format!(
+            "{} -{}{operator} {}",
+            self.transpile_expr(lhs)?,
+            if numeric { "" } else { "c" },
+            self.transpile_expr(rhs)?,
+        ))
+    }
+
+    /// Lowered to a chain of `if`s rather than a `switch`, which is a loop of its own
+    /// in PowerShell, so a `break` or `continue` in an arm would only leave the `switch`
+    fn transpile_match(&mut self, value: &WithCursor<Expression>, arms: &MatchCase) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        // the value is only evaluated once
+        let value = match &value.value {
+            Expression::Identifier(identifier) => format!("${}", self.ctx.variable_name(identifier)),
+            expr => {
+                writeln!(s, "$__tsh_match = {}", self.transpile_arg(expr).at(value)?)?;
+                String::from("$__tsh_match")
+            },
+        };
+
+        let mut chained = false;
+
+        for arm in arms {
+            // like `*` in bash, a wildcard matches whatever the arms before it didn't, so the arms after it never run
+            if arm.patterns.iter().any(|pattern| pattern.value == MatchPattern::Wildcard) {
+                let body = self.transpile_arm(&arm.value.value)?;
+
+                match chained {
+                    true => write!(s, "}} else {{\n{}", indented(&body))?,
+                    false => write!(s, "{body}")?,
+                }
+
+                break;
+            }
+
+            let patterns = arm.patterns.iter()
+                .map(|pattern| self.transpile_pattern(&pattern.value))
+                .collect::<TranspilerResult<Vec<_>>>()?;
+
+            let keyword = if chained { "} elseif" } else { "if" };
+
+            match patterns.as_slice() {
+                [pattern] => writeln!(s, "{keyword} ({value} -ceq {pattern}) {{")?,
+                patterns => writeln!(s, "{keyword} ({value} -cin {}) {{", patterns.join(", "))?,
+            }
+
+            write!(s, "{}", indented(&self.transpile_arm(&arm.value.value)?))?;
+            chained = true;
+        }
+
+        // without a chain, the match is the body of a wildcard arm, whose newline is left to the statement
+        match chained {
+            true => write!(s, "}}")?,
+            false => s.truncate(s.trim_end_matches('\n').len()),
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_arm(&mut self, body: &Expression) -> TranspilerResult<String> {
+        Ok(match body {
+            Expression::Block(block) => self.transpile_scope(ScopeKind::Block, block)?,
+            expr => format!("{}\n", self.transpile_expr_stmt(expr)?),
+        })
+    }
+
+    fn transpile_pattern(&mut self, pattern: &MatchPattern) -> TranspilerResult<String> {
+        match pattern {
+            MatchPattern::Literal(literal) => self.transpile_literal(literal),
+            MatchPattern::Wildcard => unreachable!("wildcard arms are lowered to `else`"),
+        }
+    }
+
+    fn transpile_command(&mut self, command: &ShellCommand) -> TranspilerResult<String> {
+        let mut s = verbatim(&command.name);
+
+        for word in &command.args {
+            write!(s, " {}", self.transpile_word(word)?)?;
+        }
+
+        Ok(s)
+    }
+
+    /// Transpiles a shell word to a single argument of a native command, as it was written in shell syntax
+    fn transpile_word(&mut self, word: &ShellWord) -> TranspilerResult<String> {
+        Ok(match word.as_slice() {
+            [ShellWordPart::Literal(text)] => {
+                let text = unescape(text, |_| true);
+
+                if text.chars().all(|char| char.is_ascii_alphanumeric() || matches!(char, '-' | '_' | '.' | '/' | ':' | '=' | '+' | '%')) {
+                    text
+                } else {
+                    verbatim(&PowerShellQuoting::quote(&text))
+                }
+            },
+            [ShellWordPart::SingleQuoted(text)] => verbatim(&PowerShellQuoting::quote(text)),
+            [ShellWordPart::Interpolation(expr)] => self.transpile_arg(&expr.value)?,
+            // an array is passed to a native command as separate arguments
+            [ShellWordPart::UnquotedInterpolation(expr)] => format!("(-split {})", self.transpile_arg(&expr.value)?),
+            parts => {
+                let mut s = String::from('"');
+
+                for part in parts {
+                    match part {
+                        ShellWordPart::Literal(text) => s.push_str(&verbatim(&PowerShellQuoting::escape_double_quoted(&unescape(text, |_| true)))),
+                        ShellWordPart::SingleQuoted(text) => s.push_str(&verbatim(&PowerShellQuoting::escape_double_quoted(text))),
+                        ShellWordPart::DoubleQuoted(parts) => {
+                            for part in parts {
+                                match part {
+                                    StringPart::Literal(text) => {
+                                        let text = unescape(text, |char| matches!(char, '$' | '`' | '"' | '\\'));
+                                        s.push_str(&verbatim(&PowerShellQuoting::escape_double_quoted(&text)));
+                                    },
+                                    StringPart::Expression(expr) => s.push_str(&self.transpile_embedded(&expr.value)?),
+                                }
+                            }
+                        },
+                        ShellWordPart::Interpolation(expr) | ShellWordPart::UnquotedInterpolation(expr) => {
+                            s.push_str(&self.transpile_embedded(&expr.value)?);
+                        },
+                    }
+                }
+
+                s.push('"');
+                s
+            },
+        })
+    }
+
+    /// Transpiles an expression passed as an argument, where anything
+    /// but a single value has to be grouped to be evaluated
+    fn transpile_arg(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        match expression {
+            Expression::Identifier(_)
+            | Expression::Literal(_)
+            | Expression::Group(_)
+            | Expression::ShellCommand(_)
+            | Expression::InterpolatedString(_)
+            | Expression::FunctionCall(_)
+            | Expression::Block(_)
+            | Expression::If(_)
+            | Expression::Match(_) => self.transpile_expr(expression),
+            _ => Ok(format!("({})", self.transpile_expr(expression)?)),
+        }
+    }
+
+    /// Transpiles the inside of a double quoted string
+    fn transpile_interpolation(&mut self, parts: &InterpolatedString) -> TranspilerResult<String> {
+        let mut s = String::new();
+
+        for part in parts {
+            match part {
+                StringPart::Literal(text) => s.push_str(&verbatim(&PowerShellQuoting::escape_double_quoted(text))),
+                StringPart::Expression(expr) => s.push_str(&self.transpile_embedded(&expr.value)?),
+            }
+        }
+
+        Ok(s)
+    }
+
+    /// Transpiles an expression embedded in a double quoted string
+    fn transpile_embedded(&mut self, expression: &Expression) -> TranspilerResult<String> {
+        Ok(match expression {
+            Expression::Identifier(identifier) => format!("${{{}}}", self.ctx.variable_name(identifier)),
+            Expression::Literal(literal) => verbatim(&PowerShellQuoting::escape_double_quoted(&literal_text(literal))),
+            Expression::Group(expr) => self.transpile_embedded(expr)?,
+            Expression::InterpolatedString(parts) => self.transpile_interpolation(parts)?,
+            _ => format!("$({})", self.transpile_expr(expression)?),
+        })
+    }
+
+    fn transpile_call(&mut self, name: &str, args: &[WithCursor<Expression>]) -> TranspilerResult<String> {
+        self.ctx.check_call(name, args)?;
+
+        let mut s = self.ctx.function_name(name);
+
+        for arg in args {
+            write!(s, " {}", self.transpile_arg(&arg.value).at(arg)?)?;
+        }
+
+        Ok(s)
+    }
+
+    fn transpile_assignment(&mut self, target: &WithCursor<Expression>, operator: &WithCursor<AssignmentOperator>, value: &WithCursor<Expression>) -> TranspilerResult<String> {
+        let Expression::Identifier(identifier) = &target.value else {
+            return Err(TranspilerErrorKind::unsupported("assigning to anything but a variable").at(target));
+        };
+        let name = self.ctx.variable_name(identifier);
+
+        let operator = match operator.value {
+            AssignmentOperator::Assign => "=",
+            AssignmentOperator::PlusAssign => "+=",
+            AssignmentOperator::MinusAssign => "-=",
+            AssignmentOperator::MultiplyAssign => "*=",
+            AssignmentOperator::DivideAssign if self.ctx.types.variable(identifier) != ValueType::String => {
+                let divisor = self.transpile_expr(&value.value).at(value)?;
+                return Ok(format!("${name} = [Math]::Truncate(${name} / {divisor})"));
+            },
+            AssignmentOperator::DivideAssign => "/=",
+        };
+
+        Ok(format!("${name} {operator} {}", self.transpile_expr(&value.value).at(value)?))
+    }
+
+    fn transpile_arithmetic(&mut self, lhs: &WithCursor<Expression>, operator: ArithmeticOperator, rhs: &WithCursor<Expression>) -> TranspilerResult<String> {
+        let lhs_type = self.ctx.types.infer(&lhs.value);
+        let rhs_type = self.ctx.types.infer(&rhs.value);
+
+        let mut lhs = self.transpile_expr(&lhs.value)?;
+        let rhs = self.transpile_expr(&rhs.value)?;
+
+        Ok(match operator {
+            // integers divide into decimals, the fraction is dropped like the shells do
+            ArithmeticOperator::Divide if lhs_type != ValueType::String => format!("[Math]::Truncate({lhs} / {rhs})"),
+            ArithmeticOperator::Add if rhs_type == ValueType::String && lhs_type != ValueType::String => {
+                // the left operand decides whether `+` adds or concatenates
+                lhs.insert_str(0, "[string]");
+                format!("{lhs} + {rhs}")
+            },
+            _ => format!("{lhs} {} {rhs}", match operator {
+                ArithmeticOperator::Add => "+",
+                ArithmeticOperator::Subtract => "-",
+                ArithmeticOperator::Multiply => "*",
+                ArithmeticOperator::Divide => "/",
+            }),
+        })
+    }
+
+    fn transpile_unary(&mut self, operator: UnaryOperator, operand: &WithCursor<Expression>) -> TranspilerResult<String> {
+        let value = self.transpile_expr(&operand.value)?;
+
+        Ok(match operator {
+            // `--` would otherwise be read as a decrement
+            UnaryOperator::Negative if value.starts_with('-') => format!("-({value})"),
+            UnaryOperator::Negative => format!("-{value}"),
+            UnaryOperator::Not if self.ctx.types.infer(&operand.value) == ValueType::Integer => format!("[int](-not {value})"),
+            UnaryOperator::Not => format!("-not {value}"),
+        })
+    }
+
+    fn transpile_literal(&mut self, literal: &Literal) -> TranspilerResult<String> {
+        Ok(match literal {
+            Literal::String(string) => verbatim(&PowerShellQuoting::quote(string)),
+            Literal::Boolean(bool) => format!("${bool}"),
+            Literal::Integer(int) => int.to_string(),
+        })
+    }
+}
+
+/// The type constraint of a parameter
+fn type_name(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::Integer => "[int]",
+        ValueType::Boolean => "[bool]",
+        ValueType::String => "[string]",
+        ValueType::Unknown => "",
+    }
+}
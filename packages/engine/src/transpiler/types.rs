@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use crate::parser::ast::{split_trailing_comments, Block, Expression, Function, Literal, ProgramTree, Statement, UnaryOperator, Variable};
+
+/// The kind of value an expression evaluates to, used by targets to pick between
+/// operations that behave differently for strings and numbers
+#[derive(lang_macro::EnumVariants, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueType {
+    Integer,
+    Boolean,
+    String,
+    #[default]
+    Unknown,
+}
+
+impl ValueType {
+    /// Resolves an explicit type annotation, e.g. `var x: Int`
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "Int" => Self::Integer,
+            "Bool" => Self::Boolean,
+            "String" => Self::String,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Combines the types of two declarations of the same name
+    fn merge(self, other: Self) -> Self {
+        if self == other {
+            self
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+/// Types are shown the way they are written in an annotation
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Integer => "Int",
+            Self::Boolean => "Bool",
+            Self::String => "String",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+/// What a call site needs to know about a declared function
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FunctionSignature {
+    pub return_type: ValueType,
+    pub parameters: Vec<ValueType>,
+    /// Parameters without a default value, which always come first
+    pub required: usize,
+}
+
+impl FunctionSignature {
+    fn create(func: &Function, table: &TypeTable) -> Self {
+        let parameters = func.parameters.as_deref().unwrap_or_default();
+
+        Self {
+            return_type: match &func.strict_type {
+                Some(strict_type) => ValueType::from_name(strict_type),
+                None => {
+                    let body = &func.body.value;
+
+                    // a command is both a value and a status, so a command tail says nothing of the type
+                    let tail = match split_trailing_comments(body).0.last() {
+                        Some(Statement::Tail(expr)) if !matches!(expr.value, Expression::ShellCommand(_)) => Some(table.infer(&expr.value)),
+                        _ => None,
+                    };
+
+                    table.returned_type(body).into_iter().chain(tail).reduce(ValueType::merge).unwrap_or_default()
+                },
+            },
+            parameters: parameters.iter().map(|param| table.declared_type(param)).collect(),
+            required: parameters.iter().take_while(|param| param.value.is_none()).count(),
+        }
+    }
+
+    /// Whether the function can be called with the given amount of arguments
+    pub fn accepts(&self, count: usize) -> bool {
+        (self.required..=self.parameters.len()).contains(&count)
+    }
+}
+
+/// Types of the variables and functions declared in a program.
+///
+/// Bash variables are dynamically scoped, so names are tracked for the whole program.
+/// A name declared more than once with different types resolves to [`ValueType::Unknown`]
+#[derive(Debug, Default)]
+pub struct TypeTable {
+    variables: HashMap<String, ValueType>,
+    functions: HashMap<String, FunctionSignature>,
+}
+
+impl TypeTable {
+    pub fn create(tree: &ProgramTree) -> Self {
+        let mut table = Self::default();
+        table.collect_block(tree);
+        table
+    }
+
+    pub fn variable(&self, name: &str) -> ValueType {
+        self.variables.get(name).copied().unwrap_or_default()
+    }
+
+    /// The signature of a function declared in the program, functions coming from
+    /// the shell or included scripts aren't known
+    pub fn function(&self, name: &str) -> Option<&FunctionSignature> {
+        self.functions.get(name)
+    }
+
+    /// Infers the type of the value an expression evaluates to
+    pub fn infer(&self, expression: &Expression) -> ValueType {
+        match expression {
+            Expression::Literal(literal) => match literal.as_ref() {
+                Literal::Integer(_) => ValueType::Integer,
+                Literal::Boolean(_) => ValueType::Boolean,
+                Literal::String(_) => ValueType::String,
+            },
+            Expression::Identifier(identifier) => self.variable(identifier),
+            Expression::Group(expr) => self.infer(expr),
+            Expression::Unary(unary) => match unary.0.value {
+                UnaryOperator::Negative => ValueType::Integer,
+                UnaryOperator::Not if self.infer(&unary.1.value) == ValueType::Integer => ValueType::Integer,
+                UnaryOperator::Not => ValueType::Boolean,
+            },
+            Expression::Arithmetic(arithmetic) => {
+                let lhs = self.infer(&arithmetic.0.value);
+                let rhs = self.infer(&arithmetic.2.value);
+
+                if lhs == ValueType::String || rhs == ValueType::String {
+                    ValueType::String
+                } else {
+                    ValueType::Integer
+                }
+            },
+            Expression::Logical(_) => ValueType::Boolean,
+            Expression::Assignment(assignment) => self.infer(&assignment.2.value),
+            Expression::ShellCommand(_) | Expression::InterpolatedString(_) => ValueType::String,
+            Expression::FunctionCall(call) => self.function(&call.0).map(|func| func.return_type).unwrap_or_default(),
+            Expression::Block(block) => self.block_type(block),
+            Expression::If(if_expr) => match &if_expr.2 {
+                Some(else_block) => self.block_type(&if_expr.1.value).merge(self.block_type(&else_block.value)),
+                None => ValueType::Unknown,
+            },
+            Expression::Match(match_expr) => match_expr.1.iter()
+                .map(|arm| self.infer(&arm.value.value))
+                .reduce(ValueType::merge)
+                .unwrap_or_default(),
+            _ => ValueType::Unknown,
+        }
+    }
+
+    /// The type of the value a block evaluates to, which is the one of its tail expression
+    pub fn block_type(&self, block: &Block) -> ValueType {
+        match split_trailing_comments(block).0.last() {
+            Some(Statement::Tail(expr)) => self.infer(&expr.value),
+            _ => ValueType::Unknown,
+        }
+    }
+
+    /// The type of the values returned from a block through `return` statements, if it returns any
+    fn returned_type(&self, block: &Block) -> Option<ValueType> {
+        block.iter()
+            .filter_map(|statement| match statement {
+                Statement::Return(value) => value.value.as_ref().map(|value| self.infer(&value.value)),
+                Statement::While(while_loop) => self.returned_type(&while_loop.value.1.value),
+                Statement::For(for_loop) => self.returned_type(&for_loop.value.2.value),
+                Statement::If(expr) | Statement::Tail(expr) => match &expr.value {
+                    Expression::If(if_expr) => [Some(&if_expr.1), if_expr.2.as_ref()].into_iter()
+                        .flatten()
+                        .filter_map(|block| self.returned_type(&block.value))
+                        .reduce(ValueType::merge),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .reduce(ValueType::merge)
+    }
+
+    fn declare_variable(&mut self, name: &str, value_type: ValueType) {
+        let value_type = match self.variables.get(name) {
+            Some(existing) => existing.merge(value_type),
+            None => value_type,
+        };
+
+        self.variables.insert(name.to_string(), value_type);
+    }
+
+    fn declared_type(&self, var: &Variable) -> ValueType {
+        match (&var.strict_type, &var.value) {
+            (Some(strict_type), _) => ValueType::from_name(strict_type),
+            (None, Some(value)) => self.infer(&value.value),
+            (None, None) => ValueType::Unknown,
+        }
+    }
+
+    fn declare(&mut self, var: &Variable) {
+        self.declare_variable(&var.name, self.declared_type(var));
+    }
+
+    fn collect_block(&mut self, block: &Block) {
+        for statement in block {
+            self.collect_stmt(statement);
+        }
+    }
+
+    fn collect_stmt(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Variable(var) | Statement::Constant(var) => {
+                let var = &var.value;
+                self.declare(var);
+
+                if let Some(value) = &var.value {
+                    self.collect_expr(&value.value);
+                }
+            },
+            Statement::Function(func) => {
+                let func = &func.value;
+
+                for parameter in func.parameters.iter().flatten() {
+                    self.declare(parameter);
+                }
+
+                self.collect_block(&func.body.value);
+
+                // the body is collected first, so the values it returns can be inferred
+                self.functions.insert(func.name.clone(), FunctionSignature::create(func, self));
+            },
+            Statement::For(for_loop) => {
+                let (variable, iterable, block) = &for_loop.value;
+                let value_type = match &iterable.value {
+                    Expression::Range(_) => ValueType::Integer,
+                    _ => ValueType::String,
+                };
+
+                self.declare_variable(&variable.name, value_type);
+                self.collect_block(&block.value);
+            },
+            Statement::While(while_loop) => self.collect_block(&while_loop.value.1.value),
+            Statement::If(expr) | Statement::Match(expr) | Statement::Expression(expr) | Statement::Tail(expr) => self.collect_expr(&expr.value),
+            Statement::Return(value) => {
+                if let Some(value) = &value.value {
+                    self.collect_expr(&value.value);
+                }
+            },
+            Statement::Continue(_) | Statement::Break(_) | Statement::Include(_) | Statement::Comment(_) => {},
+        }
+    }
+
+    fn collect_expr(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Block(block) => self.collect_block(block),
+            Expression::If(if_expr) => {
+                self.collect_block(&if_expr.1.value);
+
+                if let Some(else_block) = &if_expr.2 {
+                    self.collect_block(&else_block.value);
+                }
+            },
+            Expression::Match(match_expr) => {
+                for arm in &match_expr.1 {
+                    self.collect_expr(&arm.value.value);
+                }
+            },
+            _ => {},
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Layout of the code written by the targets.
+//!
+//! Targets lower nodes to strings, which are put together from the inside out, so the depth a block
+//! ends up at isn't known while it's lowered. Instead, targets mark a nested block with [`indented`],
+//! and the [`CodeWriter`] indents its lines once the whole statement is written out. Text of the script,
+//! such as the one of a multiline string, is kept out of it with [`verbatim`]
+
+use std::io::Write;
+
+// characters from the private use area of unicode, which the text of a script can still hold,
+// so the ones it holds are escaped with `LITERAL` by [`verbatim`]
+pub(super) const MARK_START: char = '\u{F8F0}';
+pub(super) const MARK_END: char = '\u{F8F1}';
+const INDENT: char = '\u{F8F2}';
+const DEDENT: char = '\u{F8F3}';
+const VERBATIM_NEWLINE: char = '\u{F8F4}';
+/// Writes the character after it as it is
+const LITERAL: char = '\u{F8F5}';
+
+/// What a level of nesting is indented with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Indent {
+    /// Nested code is written as flat as the code around it
+    #[default]
+    None,
+    Spaces(usize),
+    Tabs,
+}
+
+impl Indent {
+    fn text(self) -> String {
+        match self {
+            Self::None => String::new(),
+            Self::Spaces(width) => " ".repeat(width),
+            Self::Tabs => String::from("\t"),
+        }
+    }
+}
+
+/// Nests a block of code a level deeper than the code around it
+pub fn indented(code: &str) -> String {
+    if code.is_empty() {
+        return String::new();
+    }
+
+    format!("{INDENT}{code}{DEDENT}")
+}
+
+/// Keeps text of the script as it is. Its line breaks don't indent the lines after them,
+/// and the characters the writer would take for its own are written out like any other.
+/// Every piece of text coming from a script goes through this, after being quoted
+pub fn verbatim(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '\n' => s.push(VERBATIM_NEWLINE),
+            MARK_START..=LITERAL => {
+                s.push(LITERAL);
+                s.push(char);
+            },
+            char => s.push(char),
+        }
+    }
+
+    s
+}
+
+/// Writes code to the output, keeping track of the line and the depth it's at
+pub(super) struct CodeWriter {
+    indent: String,
+    depth: usize,
+    line_start: bool,
+    line: usize,
+}
+
+impl CodeWriter {
+    pub fn create(indent: Indent) -> Self {
+        Self {
+            indent: indent.text(),
+            depth: 0,
+            line_start: true,
+            line: 1,
+        }
+    }
+
+    /// The line being written, counted from 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Writes code with its nested blocks indented, and gives back the marks of the statements
+    /// taken out of it along with the lines they were at
+    pub fn write(&mut self, out: &mut impl Write, code: &str) -> std::io::Result<Vec<(usize, usize)>> {
+        let mut text = String::with_capacity(code.len());
+        let mut marks = Vec::new();
+        let mut chars = code.chars();
+
+        while let Some(char) = chars.next() {
+            match char {
+                INDENT => self.depth += 1,
+                DEDENT => self.depth = self.depth.saturating_sub(1),
+                MARK_START => {
+                    let id: String = chars.by_ref().take_while(|char| *char != MARK_END).collect();
+
+                    // only the context writes marks, the ones in the text of the script are escaped
+                    if let Ok(id) = id.parse() {
+                        marks.push((self.line, id));
+                    }
+                },
+                // empty lines are left without indentation
+                '\n' => {
+                    text.push('\n');
+                    self.line += 1;
+                    self.line_start = true;
+                },
+                VERBATIM_NEWLINE => {
+                    text.push('\n');
+                    self.line += 1;
+                },
+                LITERAL => {
+                    if let Some(char) = chars.next() {
+                        self.push(&mut text, char);
+                    }
+                },
+                char => self.push(&mut text, char),
+            }
+        }
+
+        out.write_all(text.as_bytes())?;
+        Ok(marks)
+    }
+
+    /// Pushes a character of a line, indenting the line first when it's the first one
+    fn push(&mut self, text: &mut String, char: char) {
+        if self.line_start {
+            text.push_str(&self.indent.repeat(self.depth));
+            self.line_start = false;
+        }
+
+        text.push(char);
+    }
+}
+
+/// Whether there is an empty line between two positions of the source, counted in characters
+/// from its first one that isn't whitespace, like the lexer does
+pub(super) fn has_blank_line(code: &str, from: u32, to: u32) -> bool {
+    let between: String = code.trim_start().chars()
+        .skip(from as usize)
+        .take(to.saturating_sub(from) as usize)
+        .collect();
+
+    let lines: Vec<_> = between.split('\n').collect();
+
+    // the first and the last line are shared with the code around it
+    lines.len() > 2 && lines[1..lines.len() - 1].iter().any(|line| line.trim().is_empty())
+}
@@ -0,0 +1,13 @@
+set name 'x'
+git status --short
+grep -r "--include=*.$name" 'a  b' '$HOME' 'a b'
+curl "https://example.com/$name"
+ls (string split -n ' ' -- "$name") "it's $name"
+set out "$(git rev-parse HEAD)"
+set files "$(ls -a)"'!'
+if test -d .git
+set branch "$(git branch --show-current)"
+if test "$branch" = "$(git config init.defaultBranch)"
+echo default
+end
+end
@@ -0,0 +1,38 @@
+set count 0
+while test "$count" -lt 10
+set count (math -s0 "$count + 1")
+if test "$count" -eq 2
+continue
+end
+end
+for i in (seq 0 4)
+if test "$i" -gt 3
+break
+else if test "$i" -eq 1
+echo one
+else
+echo "$i"
+end
+end
+for i in (seq 1 "$count")
+echo "$i"
+end
+for item in $items
+echo "$item"
+end
+for file in 'src/'*'.rs'
+echo "$file"
+end
+for line in (git log --oneline)
+echo "$line"
+end
+set ready "$(test "$count" -ge 10 || begin; test "$count" -lt 0 && test "$count" -ne 5; end && echo true || echo false)"
+set label "$(if test "$ready" = true
+printf '%s\n' 'ready'
+else
+printf '%s\n' 'waiting'
+end)"
+set total "$(
+set x 2
+printf '%s\n' (math -s0 "$x * 3")
+)"
@@ -0,0 +1,24 @@
+function greet --argument-names name greeting
+set -q argv[2]; or set greeting 'Hello'
+echo "$greeting, $name"
+end
+function add --argument-names a b
+printf '%s\n' (math -s0 "$a + $b")
+end
+function is_even --argument-names n
+test (math -s0 "$n / 2 * 2") -eq "$n" && return 0
+return 1
+end
+function sign --argument-names n
+if test "$n" -lt 0
+printf '%s\n' 'negative'
+else
+printf '%s\n' 'positive'
+end
+end
+greet 'world'
+set total (math -s0 "$(add 1 2) * 2")
+if is_even "$total" && not is_even "$(add "$total" 1)"
+echo even
+end
+echo "$(add "$total" 1) $(sign "$total")"
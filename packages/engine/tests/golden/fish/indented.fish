@@ -0,0 +1,32 @@
+set greeting 'hello
+world'
+function greet --argument-names name
+    for i in (seq 0 1)
+        if test "$i" -eq 0
+            echo "$greeting"
+        else if test "$i" -eq 1
+            echo "$name"
+        else
+            break
+        end
+    end
+end
+function kind --argument-names x
+    switch "$x"
+        case 1
+            echo one
+        case '*'
+            set -l y "$x"
+            echo many "$y"
+    end
+end
+set tries 0
+while test "$tries" -lt 3
+    set tries (math -s0 "$tries + 1")
+end
+set label "$(if test "$tries" -eq 3
+    printf '%s\n' 'done'
+else
+    printf '%s\n' 'failed'
+end)"
+greet "$label"
@@ -0,0 +1,16 @@
+switch "$level"
+case 1 2
+echo low
+case 3
+echo high
+case '*'
+echo unknown
+end
+set user "$REPLY"
+set msg "$(switch "$user"
+case 'Admin'
+printf '%s\n' 'Access granted.'
+case '*'
+printf '%s\n' 'Denied.'
+end)"
+echo "$msg"
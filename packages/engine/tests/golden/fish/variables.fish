@@ -0,0 +1,12 @@
+source 'lib/utils.fish'
+set LIMIT 10
+set name 'it\'s'
+set count (math -s0 "1 + 2 * 3 - 4 / 2")
+set ratio (math -s0 "($count + 1) / -(-2)")
+set empty 0
+set flag (math -s0 "$(test "$empty" -eq 0 && echo 1 || echo 0)")
+set greeting "Hello $name, \$HOME `n $(math -s0 "$count * 2")"
+set label "$name"'!'
+set mixed "$count"'?'
+set count (math -s0 "$count + 1")
+set count (math -s0 "$count / 2")
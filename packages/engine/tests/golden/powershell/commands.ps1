@@ -0,0 +1,13 @@
+$name = 'x'
+git status --short
+grep -r "--include=*.${name}" 'a  b' '$HOME' 'a b'
+curl "https://example.com/${name}"
+ls (-split $name) "it's ${name}"
+$out = ((git rev-parse HEAD) -join "`n")
+$files = ((ls -a) -join "`n") + '!'
+if ($(test -d .git | Out-Host; $LASTEXITCODE -eq 0)) {
+$branch = ((git branch --show-current) -join "`n")
+if ($branch -ceq ((git config init.defaultBranch) -join "`n")) {
+echo default
+}
+}
@@ -0,0 +1,38 @@
+$count = 0
+while ($count -lt 10) {
+$count += 1
+if ($count -eq 2) {
+continue
+}
+}
+for ($i = 0; $i -lt 5; $i++) {
+if ($i -gt 3) {
+break
+} elseif ($i -eq 1) {
+echo one
+} else {
+echo $i
+}
+}
+for ($i = 1; $i -le $count; $i++) {
+echo $i
+}
+foreach ($item in $items) {
+echo $item
+}
+foreach ($file in (Resolve-Path -Path 'src/*.rs' -Relative -ErrorAction Ignore)) {
+echo $file
+}
+foreach ($line in (git log --oneline)) {
+echo $line
+}
+$ready = $count -ge 10 -or ($count -lt 0 -and $count -ne 5)
+$label = $(if ($ready) {
+'ready'
+} else {
+'waiting'
+})
+$total = $(
+$x = 2
+$x * 3
+)
@@ -0,0 +1,26 @@
+function greet {
+param([string]$name, $greeting = 'Hello')
+echo "${greeting}, ${name}"
+}
+function add {
+param([int]$a, [int]$b)
+$a + $b
+}
+function is_even {
+param([int]$n)
+return [Math]::Truncate($n / 2) * 2 -eq $n
+}
+function sign {
+param([int]$n)
+if ($n -lt 0) {
+'negative'
+} else {
+'positive'
+}
+}
+greet 'world'
+$total = (add 1 2) * 2
+if ((is_even $total) -and -not (is_even (add $total 1))) {
+echo even
+}
+echo "$((add $total 1)) $((sign $total))"
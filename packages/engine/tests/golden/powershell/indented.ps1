@@ -0,0 +1,33 @@
+$greeting = 'hello
+world'
+function greet {
+    param([string]$name)
+    for ($i = 0; $i -lt 2; $i++) {
+        if ($i -eq 0) {
+            echo $greeting
+        } elseif ($i -eq 1) {
+            echo $name
+        } else {
+            break
+        }
+    }
+}
+function kind {
+    param([int]$x)
+    if ($x -ceq 1) {
+        echo one
+    } else {
+        $y = $x
+        echo many $y
+    }
+}
+$tries = 0
+while ($tries -lt 3) {
+    $tries += 1
+}
+$label = $(if ($tries -eq 3) {
+    'done'
+} else {
+    'failed'
+})
+greet $label
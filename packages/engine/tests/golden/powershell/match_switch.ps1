@@ -0,0 +1,20 @@
+if ($level -cin 1, 2) {
+echo low
+} elseif ($level -ceq 3) {
+echo high
+} else {
+echo unknown
+}
+$user = $REPLY
+$msg = $(if ($user -ceq 'Admin') {
+'Access granted.'
+} else {
+'Denied.'
+})
+echo $msg
+echo any
+if ($level -ceq 1) {
+echo one
+} else {
+echo other
+}
@@ -0,0 +1,12 @@
+. 'lib/utils.ps1'
+Set-Variable -Name LIMIT -Value 10 -Option ReadOnly
+$name = 'it''s'
+$count = 1 + 2 * 3 - [Math]::Truncate(4 / 2)
+$ratio = [Math]::Truncate(($count + 1) / -(-2))
+$empty = 0
+$flag = [int](-not $empty)
+$greeting = "Hello ${name}, `$HOME ``n $($count * 2)"
+$label = $name + '!'
+$mixed = [string]$count + '?'
+$count += 1
+$count = [Math]::Truncate($count / 2)
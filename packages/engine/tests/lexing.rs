@@ -40,7 +40,7 @@ macro_rules! custom_assert {
 }
 
 mod basic_syntax {
-    use lang_engine::lexer::tokens::{LexerLiteral, LexerToken, LexerTokenKind};
+    use lang_engine::lexer::tokens::{LexerComment, LexerLiteral, LexerShellWordPart, LexerStringPart, LexerToken, LexerTokenKind, ShellCommand};
 
     token_list_comparison!(
         single_line_comment,
@@ -49,7 +49,13 @@ mod basic_syntax {
             value: None,
             start: Cursor::from(1, 15),
             end: Cursor::from(1, 15),
-            kind: LexerTokenKind::EOF
+            kind: LexerTokenKind::EOF,
+            trivia: vec![LexerComment {
+                text: String::from(" hello world"),
+                doc: false,
+                start: Cursor::from(1, 1),
+                end: Cursor::from(1, 15),
+            }]
         }]
     );
 
@@ -69,31 +75,41 @@ mod basic_syntax {
                 value: None,
                 start: Cursor::from(5, 4),
                 end: Cursor::from(5, 7),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: vec![LexerComment {
+                    text: String::from(" \n    this demonstrates the \n    multi line comment feature\n    of the language !!\n"),
+                    doc: false,
+                    start: Cursor::from(1, 1),
+                    end: Cursor::from(5, 3),
+                }]
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("test"))))),
                 start: Cursor::from(5, 8),
                 end: Cursor::from(5, 12),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(5, 13),
                 end: Cursor::from(5, 14),
-                kind: LexerTokenKind::Equal
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Integer(50))),
                 start: Cursor::from(5, 15),
                 end: Cursor::from(5, 17),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(5, 17),
                 end: Cursor::from(5, 17),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -106,31 +122,36 @@ mod basic_syntax {
                 value: None,
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 4),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("test"))))),
                 start: Cursor::from(1, 5),
                 end: Cursor::from(1, 9),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 10),
                 end: Cursor::from(1, 11),
-                kind: LexerTokenKind::Equal
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Integer(50))),
                 start: Cursor::from(1, 12),
                 end: Cursor::from(1, 14),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 14),
                 end: Cursor::from(1, 14),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -148,61 +169,71 @@ var my_str = \"hello world\"
                 value: None,
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 4),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("test"))))),
                 start: Cursor::from(1, 5),
                 end: Cursor::from(1, 9),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 10),
                 end: Cursor::from(1, 11),
-                kind: LexerTokenKind::Equal
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Integer(50))),
                 start: Cursor::from(1, 12),
                 end: Cursor::from(1, 14),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 14),
                 end: Cursor::from(2, 1),
-                kind: LexerTokenKind::EOL
+                kind: LexerTokenKind::EOL,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(2, 1),
                 end: Cursor::from(2, 4),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("my_str"))))),
                 start: Cursor::from(2, 5),
                 end: Cursor::from(2, 11),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(2, 12),
                 end: Cursor::from(2, 13),
-                kind: LexerTokenKind::Equal
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::String(Box::from(String::from("hello world"))))),
                 start: Cursor::from(2, 14),
                 end: Cursor::from(2, 27),
-                kind: LexerTokenKind::String
+                kind: LexerTokenKind::String,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(2, 27),
                 end: Cursor::from(2, 27),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -222,138 +253,299 @@ var test4 = $echo hello world + \"lol\"
                 value: None,
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 4),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("test1"))))),
                 start: Cursor::from(1, 5),
                 end: Cursor::from(1, 10),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 11),
                 end: Cursor::from(1, 12),
-                kind: LexerTokenKind::Equal
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
             },
             LexerToken {
-                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from((String::from("echo"), Some(String::from("hello world"))))))),
+                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand {
+                    name: String::from("echo"),
+                    args: vec![
+                        vec![LexerShellWordPart::Literal(String::from("hello"))],
+                        vec![LexerShellWordPart::Literal(String::from("world"))],
+                    ],
+                })))),
                 start: Cursor::from(1, 13),
                 end: Cursor::from(1, 30),
-                kind: LexerTokenKind::ShellCommand
+                kind: LexerTokenKind::ShellCommand,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 30),
                 end: Cursor::from(2, 1),
-                kind: LexerTokenKind::EOL
+                kind: LexerTokenKind::EOL,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(2, 1),
                 end: Cursor::from(2, 4),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("test2"))))),
                 start: Cursor::from(2, 5),
                 end: Cursor::from(2, 10),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(2, 11),
                 end: Cursor::from(2, 12),
-                kind: LexerTokenKind::Equal
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
             },
             LexerToken {
-                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from((String::from("echo"), Some(String::from("hello world"))))))),
+                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand {
+                    name: String::from("echo"),
+                    args: vec![
+                        vec![LexerShellWordPart::Literal(String::from("hello"))],
+                        vec![LexerShellWordPart::Literal(String::from("world"))],
+                    ],
+                })))),
                 start: Cursor::from(2, 13),
                 end: Cursor::from(2, 31),
-                kind: LexerTokenKind::ShellCommand
+                kind: LexerTokenKind::ShellCommand,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(2, 31),
                 end: Cursor::from(3, 1),
-                kind: LexerTokenKind::EOL
+                kind: LexerTokenKind::EOL,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(3, 1),
                 end: Cursor::from(3, 4),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("test3"))))),
                 start: Cursor::from(3, 5),
                 end: Cursor::from(3, 10),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(3, 11),
                 end: Cursor::from(3, 12),
-                kind: LexerTokenKind::Equal
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
             },
             LexerToken {
-                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from((String::from("echo"), Some(String::from("\"hello world\""))))))),
+                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand {
+                    name: String::from("echo"),
+                    args: vec![
+                        vec![LexerShellWordPart::DoubleQuoted(vec![LexerStringPart::Literal(String::from("hello world"))])],
+                    ],
+                })))),
                 start: Cursor::from(3, 13),
                 end: Cursor::from(3, 33),
-                kind: LexerTokenKind::ShellCommand
+                kind: LexerTokenKind::ShellCommand,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(3, 34),
                 end: Cursor::from(3, 35),
-                kind: LexerTokenKind::Plus
+                kind: LexerTokenKind::Plus,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::String(Box::from(String::from("lol"))))),
                 start: Cursor::from(3, 36),
                 end: Cursor::from(3, 41),
-                kind: LexerTokenKind::String
+                kind: LexerTokenKind::String,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(3, 41),
                 end: Cursor::from(4, 1),
-                kind: LexerTokenKind::EOL
+                kind: LexerTokenKind::EOL,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(4, 1),
                 end: Cursor::from(4, 4),
-                kind: LexerTokenKind::Var
+                kind: LexerTokenKind::Var,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("test4"))))),
                 start: Cursor::from(4, 5),
                 end: Cursor::from(4, 10),
-                kind: LexerTokenKind::Identifier
+                kind: LexerTokenKind::Identifier,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(4, 11),
                 end: Cursor::from(4, 12),
-                kind: LexerTokenKind::Equal
-            },
-            LexerToken {
-                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from((String::from("echo"), Some(String::from("hello world + \"lol\""))))))),
+                kind: LexerTokenKind::Equal,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand {
+                    name: String::from("echo"),
+                    args: vec![
+                        vec![LexerShellWordPart::Literal(String::from("hello"))],
+                        vec![LexerShellWordPart::Literal(String::from("world"))],
+                        vec![LexerShellWordPart::Literal(String::from("+"))],
+                        vec![LexerShellWordPart::DoubleQuoted(vec![LexerStringPart::Literal(String::from("lol"))])],
+                    ],
+                })))),
                 start: Cursor::from(4, 13),
                 end: Cursor::from(4, 38),
-                kind: LexerTokenKind::ShellCommand
+                kind: LexerTokenKind::ShellCommand,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(4, 38),
                 end: Cursor::from(4, 38),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
 }
 
+mod shell_words {
+    use lang_engine::lexer::tokens::{LexerLiteral, LexerShellWordPart, LexerStringPart, LexerToken, LexerTokenKind, ShellCommand};
+
+    token_list_comparison!(
+        quoting_and_comments,
+        "$grep -e 'a b' --file=\"$HOME/x\" a\\ b // comment",
+        [
+            LexerToken {
+                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand {
+                    name: String::from("grep"),
+                    args: vec![
+                        vec![LexerShellWordPart::Literal(String::from("-e"))],
+                        vec![LexerShellWordPart::SingleQuoted(String::from("a b"))],
+                        vec![
+                            LexerShellWordPart::Literal(String::from("--file=")),
+                            LexerShellWordPart::DoubleQuoted(vec![LexerStringPart::Literal(String::from("$HOME/x"))]),
+                        ],
+                        vec![LexerShellWordPart::Literal(String::from("a\\ b"))],
+                    ],
+                })))),
+                start: Cursor::from(1, 1),
+                end: Cursor::from(1, 48),
+                kind: LexerTokenKind::ShellCommand,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: None,
+                start: Cursor::from(1, 48),
+                end: Cursor::from(1, 48),
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
+            }
+        ]
+    );
+
+    token_list_comparison!(
+        enclosed_arguments,
+        "$echo(a (b) c) + 1",
+        [
+            LexerToken {
+                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand {
+                    name: String::from("echo"),
+                    args: vec![
+                        vec![LexerShellWordPart::Literal(String::from("a"))],
+                        vec![LexerShellWordPart::Literal(String::from("(b)"))],
+                        vec![LexerShellWordPart::Literal(String::from("c"))],
+                    ],
+                })))),
+                start: Cursor::from(1, 1),
+                end: Cursor::from(1, 15),
+                kind: LexerTokenKind::ShellCommand,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: None,
+                start: Cursor::from(1, 16),
+                end: Cursor::from(1, 17),
+                kind: LexerTokenKind::Plus,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: Some(Box::from(LexerLiteral::Integer(1))),
+                start: Cursor::from(1, 18),
+                end: Cursor::from(1, 19),
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: None,
+                start: Cursor::from(1, 19),
+                end: Cursor::from(1, 19),
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
+            }
+        ]
+    );
+
+    token_list_comparison!(
+        stray_closing_parenthesis,
+        "$echo done :)",
+        [
+            LexerToken {
+                value: Some(Box::from(LexerLiteral::ShellCommand(Box::from(ShellCommand {
+                    name: String::from("echo"),
+                    args: vec![
+                        vec![LexerShellWordPart::Literal(String::from("done"))],
+                        vec![LexerShellWordPart::Literal(String::from(":)"))],
+                    ],
+                })))),
+                start: Cursor::from(1, 1),
+                end: Cursor::from(1, 14),
+                kind: LexerTokenKind::ShellCommand,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: None,
+                start: Cursor::from(1, 14),
+                end: Cursor::from(1, 14),
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
+            }
+        ]
+    );
+
+    custom_assert!(
+        unterminated_quote,
+        "$echo 'abc",
+        (lexer) => {
+            assert!(lexer.has_errors());
+            Ok(())
+        }
+    );
+}
+
 mod integer_parsing {
     use lang_engine::lexer::tokens::{LexerLiteral, LexerToken, LexerTokenKind};
 
@@ -365,13 +557,15 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(51))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 3),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 3),
                 end: Cursor::from(1, 3),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -384,13 +578,15 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(-51))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 4),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 4),
                 end: Cursor::from(1, 4),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -403,13 +599,15 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(54))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 10),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 10),
                 end: Cursor::from(1, 10),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -422,13 +620,15 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(255))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 5),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 5),
                 end: Cursor::from(1, 5),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -441,13 +641,15 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(-255))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 6),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 6),
                 end: Cursor::from(1, 6),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -460,13 +662,15 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(12))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 5),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 5),
                 end: Cursor::from(1, 5),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -479,13 +683,15 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(12))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 5),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 5),
                 end: Cursor::from(1, 5),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -501,25 +707,29 @@ mod integer_parsing {
                 value: Some(Box::from(LexerLiteral::Integer(9_223_372_036_854_775_807))),
                 start: Cursor::from(1, 1),
                 end: Cursor::from(1, 26),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(1, 26),
                 end: Cursor::from(2, 1),
-                kind: LexerTokenKind::EOL
+                kind: LexerTokenKind::EOL,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: Some(Box::from(LexerLiteral::Integer(-9_223_372_036_854_775_807))),
                 start: Cursor::from(2, 1),
                 end: Cursor::from(2, 27),
-                kind: LexerTokenKind::Integer
+                kind: LexerTokenKind::Integer,
+                trivia: Vec::new()
             },
             LexerToken {
                 value: None,
                 start: Cursor::from(2, 27),
                 end: Cursor::from(2, 27),
-                kind: LexerTokenKind::EOF
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
             }
         ]
     );
@@ -540,3 +750,119 @@ mod integer_parsing {
         }
     );
 }
+
+mod interpolation {
+    use lang_engine::lexer::tokens::{LexerLiteral, LexerShellWordPart, LexerStringPart, LexerToken, LexerTokenKind};
+
+    token_list_comparison!(
+        interpolated_string,
+        "\"Hi #{name}!\"",
+        [
+            LexerToken {
+                value: Some(Box::from(LexerLiteral::InterpolatedString(Box::from(vec![
+                    LexerStringPart::Literal(String::from("Hi ")),
+                    LexerStringPart::Expression(vec![
+                        LexerToken {
+                            value: Some(Box::from(LexerLiteral::Identifier(Box::from(String::from("name"))))),
+                            start: Cursor::from(1, 7),
+                            end: Cursor::from(1, 11),
+                            kind: LexerTokenKind::Identifier,
+                            trivia: Vec::new()
+                        },
+                        LexerToken {
+                            value: None,
+                            start: Cursor::from(1, 11),
+                            end: Cursor::from(1, 11),
+                            kind: LexerTokenKind::EOF,
+                            trivia: Vec::new()
+                        },
+                    ]),
+                    LexerStringPart::Literal(String::from("!")),
+                ])))),
+                start: Cursor::from(1, 1),
+                end: Cursor::from(1, 14),
+                kind: LexerTokenKind::InterpolatedString,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: None,
+                start: Cursor::from(1, 14),
+                end: Cursor::from(1, 14),
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
+            }
+        ]
+    );
+
+    token_list_comparison!(
+        escaped_interpolation,
+        "\"\\#{name} #size\"",
+        [
+            LexerToken {
+                value: Some(Box::from(LexerLiteral::String(Box::from(String::from("#{name} #size"))))),
+                start: Cursor::from(1, 1),
+                end: Cursor::from(1, 17),
+                kind: LexerTokenKind::String,
+                trivia: Vec::new()
+            },
+            LexerToken {
+                value: None,
+                start: Cursor::from(1, 17),
+                end: Cursor::from(1, 17),
+                kind: LexerTokenKind::EOF,
+                trivia: Vec::new()
+            }
+        ]
+    );
+
+    custom_assert!(
+        nested_interpolation,
+        "$echo \"#{add(\"#{a}\", b)}\"",
+        (lexer) => {
+            assert!(!lexer.has_errors());
+
+            let tokens = lexer.tokens();
+            pretty_assertions::assert_eq!(tokens.len(), 2);
+            pretty_assertions::assert_eq!(tokens[0].kind, LexerTokenKind::ShellCommand);
+
+            let command = tokens[0].as_shell_command().unwrap();
+            pretty_assertions::assert_eq!(command.name, "echo");
+
+            let [word] = command.args.as_slice() else {
+                panic!("unexpected arguments {:#?}", command.args);
+            };
+
+            let [LexerShellWordPart::DoubleQuoted(parts)] = word.as_slice() else {
+                panic!("unexpected word {word:#?}");
+            };
+
+            let [LexerStringPart::Expression(expr)] = parts.as_slice() else {
+                panic!("unexpected string {parts:#?}");
+            };
+
+            pretty_assertions::assert_eq!(
+                expr.iter().map(|t| t.kind.clone()).collect::<Vec<_>>(),
+                vec![
+                    LexerTokenKind::Identifier,
+                    LexerTokenKind::LParen,
+                    LexerTokenKind::InterpolatedString,
+                    LexerTokenKind::Comma,
+                    LexerTokenKind::Identifier,
+                    LexerTokenKind::RParen,
+                    LexerTokenKind::EOF,
+                ]
+            );
+
+            Ok(())
+        }
+    );
+
+    custom_assert!(
+        unterminated_interpolation,
+        "\"#{name\"",
+        (lexer) => {
+            assert!(lexer.has_errors());
+            Ok(())
+        }
+    );
+}
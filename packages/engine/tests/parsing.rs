@@ -3,7 +3,7 @@ macro_rules! token_list_comparison {
         #[test]
         fn $name() -> lang_engine::error::EngineResult<()> {
             #[allow(unused_imports)]
-            use lang_engine::{parser::ast::{Statement, Expression, Variable, Function, Literal}, cursor::{WithCursor, Cursor}, component::ComponentErrors};
+            use lang_engine::{parser::ast::{Statement, Expression, Variable, Function, Literal, ShellCommand, ShellWordPart}, cursor::{WithCursor, Cursor}, component::ComponentErrors};
             let code = $code;
 
             // Step 1
@@ -37,15 +37,19 @@ macro_rules! token_list_comparison {
 token_list_comparison!(
     basic_variable,
     "var test = 50",
-    [Statement::Variable(Box::new(Variable {
-        name: String::from("test"),
-        strict_type: None,
-        value: Some(WithCursor {
-            value: Expression::Literal(Box::new(Literal::Integer(50))),
-            start: Cursor::from(1, 12),
-            end: Cursor::from(1, 14),
-        })
-    }))]
+    [Statement::Variable(Box::new(WithCursor::create_with(
+        Cursor::from(1, 1),
+        Cursor::from(1, 14),
+        Variable {
+            name: String::from("test"),
+            strict_type: None,
+            value: Some(WithCursor {
+                value: Expression::Literal(Box::new(Literal::Integer(50))),
+                start: Cursor::from(1, 12),
+                end: Cursor::from(1, 14),
+            })
+        }
+    )))]
 );
 
 token_list_comparison!(
@@ -55,48 +59,56 @@ for i in 0..5 {
     $echo #{i}
 }
     ",
-    [Statement::For(Box::from((
-        Variable {
-            name: String::from("i"),
-            strict_type: None,
-            value: None,
-        },
-        WithCursor::create_with(
-            Cursor::from_full(1, 10, 9),
-            Cursor::from_full(1, 14, 13),
-            Expression::Range(Box::from((
-                WithCursor::create_with(
-                    Cursor::from_full(1, 10, 9),
-                    Cursor::from_full(1, 11, 10),
-                    Expression::Literal(Box::from(Literal::Integer(0)))
-                ),
-                WithCursor::create_with(
-                    Cursor::from_full(1, 13, 12),
-                    Cursor::from_full(1, 14, 13),
-                    Expression::Literal(Box::from(Literal::Integer(5)))
-                ),
-                false
-            )))
-        ),
-        WithCursor::create_with(
-            Cursor::from_full(2, 1, 16),
-            Cursor::from_full(3, 2, 32),
-            vec![
-                Statement::Expression(Box::from(
+    [Statement::For(Box::from(WithCursor::create_with(
+        Cursor::from_full(1, 1, 0),
+        Cursor::from_full(3, 2, 32),
+        (
+            Variable {
+                name: String::from("i"),
+                strict_type: None,
+                value: None,
+            },
+            WithCursor::create_with(
+                Cursor::from_full(1, 10, 9),
+                Cursor::from_full(1, 14, 13),
+                Expression::Range(Box::from((
                     WithCursor::create_with(
-                        Cursor::from_full(2, 5, 20),
-                        Cursor::from_full(2, 15, 30),
-                        Expression::ShellCommand(
-                            Box::from((
-                                String::from("echo"),
-                                Some(
-                                    String::from("#{i}"),
-                                ),
+                        Cursor::from_full(1, 10, 9),
+                        Cursor::from_full(1, 11, 10),
+                        Expression::Literal(Box::from(Literal::Integer(0)))
+                    ),
+                    WithCursor::create_with(
+                        Cursor::from_full(1, 13, 12),
+                        Cursor::from_full(1, 14, 13),
+                        Expression::Literal(Box::from(Literal::Integer(5)))
+                    ),
+                    false
+                )))
+            ),
+            WithCursor::create_with(
+                Cursor::from_full(2, 1, 16),
+                Cursor::from_full(3, 2, 32),
+                vec![
+                    Statement::Tail(Box::from(
+                        WithCursor::create_with(
+                            Cursor::from_full(2, 5, 20),
+                            Cursor::from_full(2, 15, 30),
+                            Expression::ShellCommand(
+                                Box::from(ShellCommand {
+                                    name: String::from("echo"),
+                                    args: vec![
+                                        vec![ShellWordPart::Interpolation(WithCursor::create_with(
+                                            Cursor::from_full(2, 13, 28),
+                                            Cursor::from_full(2, 14, 29),
+                                            Expression::Identifier(Box::from(String::from("i"))),
+                                        ))],
+                                    ],
+                                }),
                             )),
-                        )),
+                        ),
                     ),
-                ),
-            ],
+                ],
+            ),
         ),
     )))]
 );
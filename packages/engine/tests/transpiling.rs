@@ -0,0 +1,2007 @@
+use lang_engine::transpiler::{TranspilerOptions, TranspilerTarget};
+
+fn transpile(target: TranspilerTarget, code: &str) -> String {
+    transpile_with(target, TranspilerOptions::default(), code)
+}
+
+fn transpile_with(target: TranspilerTarget, options: TranspilerOptions, code: &str) -> String {
+    use lang_engine::{component::ComponentErrors, transpiler::Transpiler};
+
+    let source_file = lang_engine::error::SourceFile::from(code.to_string(), None);
+    let mut lexer = lang_engine::lexer::Lexer::create(&source_file);
+    lexer.tokens();
+
+    if lexer.has_errors() {
+        println!("{:#?}", lexer.fetch_errors());
+    }
+
+    assert!(!lexer.has_errors());
+
+    let mut parser = lang_engine::parser::Parser::create(lexer.tokens(), &source_file);
+    parser.parse();
+
+    if parser.has_errors() {
+        println!("{:#?}", parser.fetch_errors());
+    }
+
+    assert!(!parser.has_errors());
+
+    let mut transpiler = Transpiler::create_with(&target, options, parser.parse(), &source_file);
+    transpiler.transpile()
+}
+
+/// Lowers the statements of a program one at a time, so the result of each of them can be looked at
+fn transpile_errors(target: TranspilerTarget, code: &str) -> Vec<lang_engine::transpiler::error::TranspilerResult<String>> {
+    use lang_engine::transpiler::{error::Locate, types::TypeTable, Target, TranspilerContext};
+
+    let source_file = lang_engine::error::SourceFile::from(code.to_string(), None);
+    let mut lexer = lang_engine::lexer::Lexer::create(&source_file);
+    let mut parser = lang_engine::parser::Parser::create(lexer.tokens(), &source_file);
+    let tree = parser.parse();
+
+    let mut transpiler = target.create(TranspilerContext::create(TypeTable::create(tree)));
+    tree.iter().map(|statement| transpiler.transpile_stmt(statement).at(&statement.span())).collect()
+}
+
+/// Runs a program through the engine, which writes the prelude along with it
+fn exec(target: TranspilerTarget, options: TranspilerOptions, code: &str) -> Result<String, lang_engine::error::EngineErrorKind> {
+    exec_in(&mut lang_engine::Engine::create_with(target, options), code)
+}
+
+fn exec_in(engine: &mut lang_engine::Engine, code: &str) -> Result<String, lang_engine::error::EngineErrorKind> {
+    let mut out = Vec::new();
+    engine.exec(code, &mut out)?;
+
+    Ok(String::from_utf8(out).unwrap())
+}
+
+macro_rules! transpile_comparison {
+    ($name:ident, $code:literal, $expected:literal) => {
+        #[test]
+        fn $name() {
+            pretty_assertions::assert_eq!($expected, super::transpile(super::TranspilerTarget::Bash, $code));
+        }
+    };
+}
+
+/// Compares the output for `golden/<dir>/<name>.tsh` to the file next to it with the extension of the target
+macro_rules! transpile_golden {
+    ($name:ident, $target:ident, $dir:literal, $extension:literal) => {
+        #[test]
+        fn $name() {
+            let code = include_str!(concat!("golden/", $dir, "/", stringify!($name), ".tsh"));
+            let expected = include_str!(concat!("golden/", $dir, "/", stringify!($name), ".", $extension));
+
+            pretty_assertions::assert_eq!(expected, super::transpile(super::TranspilerTarget::$target, code));
+        }
+    };
+
+    ($name:ident, $target:ident, $dir:literal, $extension:literal, $options:expr) => {
+        #[test]
+        fn $name() {
+            let code = include_str!(concat!("golden/", $dir, "/", stringify!($name), ".tsh"));
+            let expected = include_str!(concat!("golden/", $dir, "/", stringify!($name), ".", $extension));
+
+            pretty_assertions::assert_eq!(expected, super::transpile_with(super::TranspilerTarget::$target, $options, code));
+        }
+    };
+}
+
+mod statements {
+    transpile_comparison!(
+        variable,
+        "
+var test = 50
+var other = test
+        ",
+        "\
+test=50
+other=\"$test\"
+"
+    );
+
+    transpile_comparison!(
+        constant,
+        "@const var LIMIT = 10",
+        "\
+readonly LIMIT=10
+"
+    );
+
+    transpile_comparison!(
+        include,
+        "@include \"lib/utils.tsh\"",
+        "\
+source 'lib/utils.sh'
+"
+    );
+
+    transpile_comparison!(
+        expression,
+        "
+$echo hello world
+count = 5
+        ",
+        "\
+echo hello world
+count=5
+"
+    );
+
+    transpile_comparison!(
+        while_loop,
+        "
+while true {
+    $echo looping
+    continue
+}
+        ",
+        "\
+while true; do
+echo looping
+continue
+done
+"
+    );
+
+    transpile_comparison!(
+        for_loop,
+        "
+for i in 0..5 {
+    $echo step
+    break
+}
+for i in 1..=3 {
+    $echo step
+}
+        ",
+        "\
+for (( i = 0; i < 5; i++ )); do
+echo step
+break
+done
+for (( i = 1; i <= 3; i++ )); do
+echo step
+done
+"
+    );
+
+    transpile_comparison!(
+        for_range_bounds,
+        "
+var count = 10
+for i in count - 5..count * 2 {
+    $echo #{i}
+}
+for i in 0..=(count) {
+    $echo #{i}
+}
+        ",
+        "\
+count=10
+for (( i = count - 5; i < count * 2; i++ )); do
+echo \"${i}\"
+done
+for (( i = 0; i <= (count); i++ )); do
+echo \"${i}\"
+done
+"
+    );
+
+    transpile_comparison!(
+        for_collections,
+        "
+for arg in BASH_ARGV {
+    $echo #{arg}
+}
+for file in \"src/my files/*.[ch]\" {
+    $echo #{file}
+}
+for line in $git(log --oneline) {
+    $echo #{line}
+}
+        ",
+        "\
+for arg in \"${BASH_ARGV[@]}\"; do
+echo \"${arg}\"
+done
+for file in 'src/my files/'*'.'[ch]; do
+[ -e \"$file\" ] || continue
+echo \"${file}\"
+done
+while IFS= read -r line; do
+echo \"${line}\"
+done < <(git log --oneline)
+"
+    );
+
+    transpile_comparison!(
+        if_else,
+        "
+if ready {
+    $echo a
+} else if $test(-f out) {
+    $echo b
+}
+else {
+    $echo c
+}
+        ",
+        "\
+if [[ \"$ready\" == true ]]; then
+echo a
+elif test -f out; then
+echo b
+else
+echo c
+fi
+"
+    );
+
+    transpile_comparison!(
+        if_inline,
+        "
+if false: $echo a
+else: $echo b
+$echo c
+        ",
+        "\
+if false; then
+echo a
+else
+echo b
+fi
+echo c
+"
+    );
+
+    transpile_comparison!(
+        match_statement,
+        "
+match level {
+    1 || 2 => $echo low
+    3 => {
+        $echo high
+    }
+}
+        ",
+        "\
+case \"$level\" in
+1|2)
+echo low
+;;
+3)
+echo high
+;;
+esac
+"
+    );
+
+    transpile_comparison!(
+        match_wildcard,
+        "
+match $uname() {
+    \"Linux\" => $echo linux
+    \"Darwin\" || \"FreeBSD\" => $echo bsd
+    * => $echo other
+}
+        ",
+        "\
+case \"$(uname)\" in
+'Linux')
+echo linux
+;;
+'Darwin'|'FreeBSD')
+echo bsd
+;;
+*)
+echo other
+;;
+esac
+"
+    );
+
+    transpile_comparison!(
+        match_value,
+        "
+var input = REPLY
+var msg = match input {
+    \"Admin\" => \"Access granted.\"
+    * => \"Denied.\"
+}
+$echo #{msg}
+        ",
+        "\
+input=\"$REPLY\"
+msg=\"$(case \"$input\" in
+'Admin')
+echo 'Access granted.'
+;;
+*)
+echo 'Denied.'
+;;
+esac)\"
+echo \"${msg}\"
+"
+    );
+
+    transpile_comparison!(
+        function,
+        "
+fn greet() {
+    $echo hello
+    return
+}
+greet()
+        ",
+        "\
+greet() {
+echo hello
+return
+}
+greet
+"
+    );
+
+    transpile_comparison!(
+        function_parameters,
+        "
+fn greet(name: String, greeting = \"Hello\", times: Int = 1) {
+    $echo \"#{greeting}, #{name}\"
+}
+greet(\"world\")
+greet(\"world\", \"Hi\", 2)
+        ",
+        "\
+greet() {
+local name=\"$1\"
+local greeting='Hello'
+if (( $# >= 2 )); then greeting=\"$2\"; fi
+local times=1
+if (( $# >= 3 )); then times=\"$3\"; fi
+echo \"${greeting}, ${name}\"
+}
+greet 'world'
+greet 'world' 'Hi' 2
+"
+    );
+
+    transpile_comparison!(
+        function_return_convention,
+        "
+fn add(a: Int, b: Int): Int {
+    return a + b
+}
+fn is_even(n: Int): Bool {
+    return n / 2 * 2 == n
+}
+fn ready() {
+    return true
+}
+var total = add(1, 2) * 2
+if is_even(total) && ready(): $echo even
+var even = is_even(total)
+$echo \"#{add(total, 1)}\"
+        ",
+        "\
+add() {
+local a=\"$1\"
+local b=\"$2\"
+echo $(( a + b ))
+return
+}
+is_even() {
+local n=\"$1\"
+(( n / 2 * 2 == n )) && return 0
+return 1
+}
+ready() {
+return 0
+}
+total=$(( $(add 1 2) * 2 ))
+if is_even \"$total\" && ready; then
+echo even
+fi
+even=$( is_even \"$total\" && echo true || echo false )
+echo \"$(add \"$total\" 1)\"
+"
+    );
+
+    transpile_comparison!(
+        implicit_return,
+        "
+fn add(a: Int, b: Int): Int {
+    a + b
+}
+fn positive(n: Int) {
+    n > 0
+}
+fn sign(n: Int) {
+    if n < 0 {
+        \"negative\"
+    } else {
+        \"positive\"
+    }
+}
+        ",
+        "\
+add() {
+local a=\"$1\"
+local b=\"$2\"
+echo $(( a + b ))
+}
+positive() {
+local n=\"$1\"
+(( n > 0 )) && return 0
+return 1
+}
+sign() {
+local n=\"$1\"
+if (( n < 0 )); then
+echo 'negative'
+else
+echo 'positive'
+fi
+}
+"
+    );
+
+    transpile_comparison!(
+        block_values,
+        "
+var label = if $test(-t 1) {
+    \"yes\"
+} else if ok {
+    $echo maybe
+} else {
+    \"no\"
+}
+var total = {
+    var x = 2
+    x * 3
+}
+        ",
+        "\
+label=\"$(if test -t 1; then
+echo 'yes'
+elif [[ \"$ok\" == true ]]; then
+echo maybe
+else
+echo 'no'
+fi)\"
+total=\"$(
+x=2
+echo $(( x * 3 ))
+)\"
+"
+    );
+
+    transpile_comparison!(
+        discarded_values,
+        "
+var x = 1
+if x == 1 {
+    \"one\"
+} else {
+    \"#{$date()}\"
+}
+{
+    x + 1
+}
+        ",
+        "\
+x=1
+if (( x == 1 )); then
+:
+else
+: \"$(date)\"
+fi
+{
+:
+}
+"
+    );
+
+    /// Values nothing uses were run as commands, which stops a strict script
+    #[test]
+    fn discarded_values_run() {
+        use lang_engine::transpiler::{Target, TranspilerTarget};
+
+        let code = "
+var x = 1
+if x == 1 {
+    \"one\"
+} else {
+    \"other\"
+}
+{
+    x + 1
+}
+$echo done
+        ";
+
+        for (target, shell) in [(TranspilerTarget::Bash, "bash"), (TranspilerTarget::Posix, "sh")] {
+            let options = target.options();
+            let script = super::exec(target, options, code).unwrap();
+            let output = std::process::Command::new(shell).args(["-c", &script]).output().unwrap();
+
+            assert!(output.status.success(), "{script}\n{}", String::from_utf8_lossy(&output.stderr));
+            assert_eq!("done\n", String::from_utf8_lossy(&output.stdout), "{script}");
+        }
+    }
+
+    #[test]
+    fn function_arity() {
+        use lang_engine::transpiler::error::TranspilerErrorKind;
+
+        let code = "
+fn add(a: Int, b: Int = 0) {
+    return a + b
+}
+add()
+add(1, 2, 3)
+add(1)
+        ";
+
+        let results = super::transpile_errors(super::TranspilerTarget::Bash, code);
+
+        for result in &results[1..3] {
+            assert!(matches!(result, Err(TranspilerErrorKind::ArgumentCount { .. })), "{result:?}");
+        }
+
+        assert_eq!("add 1\n", results[3].as_ref().unwrap());
+    }
+
+    transpile_comparison!(
+        function_return_value,
+        "
+fn name() {
+    return \"world\"
+}
+        ",
+        "\
+name() {
+echo 'world'
+return
+}
+"
+    );
+
+    transpile_comparison!(
+        function_locals,
+        "
+var total = 0
+fn count(items: Int) {
+    var total = items
+    @const var LIMIT = 10
+    if total > LIMIT {
+        var over = total - LIMIT
+        $echo #{over}
+    }
+}
+        ",
+        "\
+total=0
+count() {
+local items=\"$1\"
+local total=\"$items\"
+local -r LIMIT=10
+if (( total > LIMIT )); then
+local over=$(( total - LIMIT ))
+echo \"${over}\"
+fi
+}
+"
+    );
+
+    #[test]
+    fn break_outside_loop() {
+        use lang_engine::transpiler::error::TranspilerErrorKind;
+
+        let code = "
+break
+while true {
+    fn skip() {
+        continue
+    }
+}
+for i in 0..3 {
+    if i == 1 {
+        break
+    }
+}
+var after = 1
+break
+        ";
+
+        let results = super::transpile_errors(super::TranspilerTarget::Bash, code);
+
+        for result in &results[..2] {
+            assert!(matches!(result, Err(TranspilerErrorKind::InvalidBreak { .. })), "{result:?}");
+        }
+
+        assert!(results[2].is_ok());
+
+        // the scopes of the loops and the function are closed again, even the ones that failed
+        assert_eq!("after=1\n", results[3].as_ref().unwrap());
+        assert!(matches!(results[4], Err(TranspilerErrorKind::InvalidBreak { .. })), "{:?}", results[4]);
+    }
+}
+
+mod arithmetic {
+    transpile_comparison!(
+        precedence,
+        "var x = 1 + 2 * 3 - 4 / 2",
+        "\
+x=$(( 1 + 2 * 3 - 4 / 2 ))
+"
+    );
+
+    transpile_comparison!(
+        group,
+        "
+var count = 1
+var size = 4
+var x = (count + 1) * (size - 2)
+        ",
+        "\
+count=1
+size=4
+x=$(( (count + 1) * (size - 2) ))
+"
+    );
+
+    transpile_comparison!(
+        unary,
+        "
+var size = 4
+var x = -size
+var y = -(-5) * 2
+var z: Int = 0
+var w = !z
+        ",
+        "\
+size=4
+x=$(( -size ))
+y=$(( -(-5) * 2 ))
+z=0
+w=$(( !z ))
+"
+    );
+
+    transpile_comparison!(
+        compound_assignment,
+        "
+var count = 0
+var step = 2
+count += 1
+count -= step
+count *= (2 + step)
+count /= 2
+        ",
+        "\
+count=0
+step=2
+(( count += 1 ))
+(( count -= step ))
+(( count *= (2 + step) ))
+(( count /= 2 ))
+"
+    );
+
+    transpile_comparison!(
+        untyped_operands,
+        "
+var next = REPLY - 1
+var limit: Int = REPLY
+limit += 1
+total += limit
+var width = -len(\"a\")
+        ",
+        "\
+next=$(( (${REPLY##*[!0-9-]*}) - 1 ))
+limit=$(( (${REPLY##*[!0-9-]*}) ))
+(( limit += 1 ))
+total=$(( (${total##*[!0-9-]*}) + (limit) ))
+width=$(( -($(__tsh_value=\"$(len 'a')\"; echo \"${__tsh_value##*[!0-9-]*}\")) ))
+"
+    );
+
+    transpile_comparison!(
+        negated_condition,
+        "
+while !done {
+    done = true
+}
+        ",
+        "\
+while ! [[ \"$done\" == true ]]; do
+done=true
+done
+"
+    );
+}
+
+mod logical {
+    transpile_comparison!(
+        numeric_comparison,
+        "
+var count = 0
+while count < 10 {
+    count += 1
+}
+if count >= 2 + 3: $echo big
+        ",
+        "\
+count=0
+while (( count < 10 )); do
+(( count += 1 ))
+done
+if (( count >= 2 + 3 )); then
+echo big
+fi
+"
+    );
+
+    transpile_comparison!(
+        string_comparison,
+        "
+var name = \"root\"
+if name == \"admin\" {
+    $echo admin
+} else if name <= \"b\" {
+    $echo early
+}
+        ",
+        "\
+name='root'
+if [[ \"$name\" == 'admin' ]]; then
+echo admin
+elif ! [[ \"$name\" > 'b' ]]; then
+echo early
+fi
+"
+    );
+
+    transpile_comparison!(
+        untyped_comparison,
+        "
+if REPLY == 1: $echo one
+if REPLY != OTHER: $echo different
+        ",
+        "\
+if [ \"$REPLY\" -eq 1 ]; then
+echo one
+fi
+if [[ \"$REPLY\" != \"$OTHER\" ]]; then
+echo different
+fi
+"
+    );
+
+    transpile_comparison!(
+        and_or_precedence,
+        "
+var a = 1
+var b = 2
+if a == 1 || b == 2 && a < b: $echo first
+if (a == 1 || b == 2) && a < b: $echo second
+if !(a == 1 && b == 2): $echo third
+        ",
+        "\
+a=1
+b=2
+if (( a == 1 )) || { (( b == 2 )) && (( a < b )); }; then
+echo first
+fi
+if { (( a == 1 )) || (( b == 2 )); } && (( a < b )); then
+echo second
+fi
+if ! { (( a == 1 )) && (( b == 2 )); }; then
+echo third
+fi
+"
+    );
+
+    transpile_comparison!(
+        truthiness,
+        "
+var count = 3
+var label = \"x\"
+var ok: Bool = true
+if count: $echo count
+if label: $echo label
+if ok: $echo ok
+        ",
+        "\
+count=3
+label='x'
+ok=true
+if (( count )); then
+echo count
+fi
+if [[ -n \"$label\" ]]; then
+echo label
+fi
+if [[ \"$ok\" == true ]]; then
+echo ok
+fi
+"
+    );
+
+    transpile_comparison!(
+        as_value,
+        "
+var a = 1
+var ok = a < 5 && a != 0
+var done = !ok
+        ",
+        "\
+a=1
+ok=$( (( a < 5 )) && (( a != 0 )) && echo true || echo false )
+done=$( ! [[ \"$ok\" == true ]] && echo true || echo false )
+"
+    );
+}
+
+mod interpolation {
+    use lang_engine::transpiler::TranspilerTarget;
+
+    transpile_comparison!(
+        string,
+        "
+var name = \"Rust\"
+var greeting = \"Hello #{name}s, \\\"#{1 + 2}\\\" $HOME\"
+        ",
+        "\
+name='Rust'
+greeting=\"Hello ${name}s, \\\"$(( 1 + 2 ))\\\" \\$HOME\"
+"
+    );
+
+    transpile_comparison!(
+        command_arguments,
+        "
+var i = 1
+$echo #{i} \"value: #{i}\" 'raw #{i}'
+$echo(#{i + 1})
+        ",
+        "\
+i=1
+echo \"${i}\" \"value: ${i}\" 'raw #{i}'
+echo \"$(( i + 1 ))\"
+"
+    );
+
+    transpile_comparison!(
+        command_substitution,
+        "
+fn name() {
+    return \"Rust\"
+}
+$echo \"return value: #{name()} in #{$pwd()}\"
+        ",
+        "\
+name() {
+echo 'Rust'
+return
+}
+echo \"return value: $(name) in $(pwd)\"
+"
+    );
+
+    #[test]
+    fn concatenation_stays_quoted() {
+        let script = super::transpile(TranspilerTarget::Bash, "
+var a = \"x   *\"
+$printf \"[%s]\\n\" \"v=#{a + a}\" #{a + \"!\"}
+        ");
+        let output = std::process::Command::new("bash").arg("-c").arg(&script).output().unwrap();
+
+        assert!(script.contains("\"v=${a}${a}\" \"${a}!\""), "{script}");
+        assert_eq!("[v=x   *x   *]\n[x   *!]\n", String::from_utf8_lossy(&output.stdout), "{script}");
+    }
+}
+
+mod commands {
+    transpile_comparison!(
+        statement,
+        "
+$git status --short
+($echo(grouped))
+        ",
+        "\
+git status --short
+echo grouped
+"
+    );
+
+    transpile_comparison!(
+        captured_value,
+        "
+var out = $git rev-parse HEAD
+var files = $ls(-a) + \"!\"
+        ",
+        "\
+out=\"$(git rev-parse HEAD)\"
+files=\"$(ls -a)\"'!'
+"
+    );
+
+    transpile_comparison!(
+        words,
+        "
+var name = \"x\"
+$grep -r --include=\"*.#{name}\" 'a  b' \\$HOME a\\ b // comment
+$echo one \\
+    two # shell comment
+$curl(https://example.com/#{name})
+        ",
+        "\
+name='x'
+grep -r --include=\"*.${name}\" 'a  b' \\$HOME a\\ b
+echo one two
+curl https://example.com/\"${name}\"
+"
+    );
+
+    transpile_comparison!(
+        condition_and_value,
+        "
+if $test(-d .git) {
+    var branch = $git(branch --show-current)
+    if branch == $git(config init.defaultBranch): $echo default
+}
+for file in $ls() {
+    $echo #{file}
+}
+        ",
+        "\
+if test -d .git; then
+branch=\"$(git branch --show-current)\"
+if [[ \"$branch\" == \"$(git config init.defaultBranch)\" ]]; then
+echo default
+fi
+fi
+while IFS= read -r file; do
+echo \"${file}\"
+done < <(ls)
+"
+    );
+}
+
+mod quoting {
+    transpile_comparison!(
+        literals,
+        "
+var x = \"a b; rm -rf ~\"
+var y = \"it's $HOME `id`\"
+$echo #{x} \"#{y}\"
+        ",
+        "\
+x='a b; rm -rf ~'
+y='it'\\''s $HOME `id`'
+echo \"${x}\" \"${y}\"
+"
+    );
+
+    transpile_comparison!(
+        unquoted_interpolation,
+        "
+var flags = \"-l -a\"
+$ls #*{flags} #{flags}
+        ",
+        "\
+flags='-l -a'
+ls ${flags} \"${flags}\"
+"
+    );
+}
+
+mod injection {
+    use lang_engine::transpiler::TranspilerTarget;
+
+    /// Runs a script with a line on its standard input, returns its output and whether it succeeded
+    fn run(shell: &str, script: &str, input: &str) -> (String, String, bool) {
+        use std::{io::Write, process::{Command, Stdio}};
+
+        let mut child = Command::new(shell)
+            .args(["-c", script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("shell should be installed");
+
+        writeln!(child.stdin.take().unwrap(), "{input}").unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap(), output.status.success())
+    }
+
+    #[test]
+    fn array_subscript_in_arithmetic() {
+        let code = "
+$read(-r line)
+var input = line
+if input == 1 {
+    $echo one
+} else {
+    $echo other
+}
+var next = input + 1
+$echo #{next}
+        ";
+
+        for (target, shell) in [(TranspilerTarget::Bash, "bash"), (TranspilerTarget::Posix, "sh")] {
+            let script = super::transpile(target, code);
+
+            assert_eq!(("one\n2\n".to_string(), String::new(), true), run(shell, &script, "1"), "{script}");
+
+            // the line is never evaluated as arithmetic, so the command in the subscript doesn't run.
+            // The quotes keep the line itself from reading as its output when an error shows it
+            let (out, err, _) = run(shell, &script, "PATH[$(echo INJ''ECTED >&2)]");
+            assert!(out.starts_with("other\n"), "{script}\n{out}");
+            assert!(err.contains("syntax error") || err.contains("arithmetic expression"), "{script}\n{err}");
+            assert!(!err.contains("INJECTED"), "{script}\n{err}");
+        }
+    }
+}
+
+mod powershell {
+    transpile_golden!(variables, PowerShell, "powershell", "ps1");
+    transpile_golden!(functions, PowerShell, "powershell", "ps1");
+    transpile_golden!(control_flow, PowerShell, "powershell", "ps1");
+    transpile_golden!(match_switch, PowerShell, "powershell", "ps1");
+    transpile_golden!(commands, PowerShell, "powershell", "ps1");
+    transpile_golden!(indented, PowerShell, "powershell", "ps1", super::TranspilerOptions { indent: lang_engine::transpiler::writer::Indent::Spaces(4), ..Default::default() });
+
+    #[test]
+    fn loop_control_in_match() {
+        let code = "
+for i in 0..5 {
+    match i {
+        1 || 2 => $echo #{i}
+        3 => {
+            break
+        }
+        * => {
+            continue
+        }
+    }
+}
+$echo done
+";
+        let bash = super::transpile(super::TranspilerTarget::Bash, code);
+        let output = std::process::Command::new("bash").arg("-c").arg(&bash).output().unwrap();
+        assert_eq!("1\n2\ndone\n", String::from_utf8_lossy(&output.stdout), "{bash}");
+
+        // `break` and `continue` inside a `switch` would only leave the `switch`
+        let powershell = super::transpile(super::TranspilerTarget::PowerShell, code);
+        pretty_assertions::assert_eq!("\
+for ($i = 0; $i -lt 5; $i++) {
+if ($i -cin 1, 2) {
+echo $i
+} elseif ($i -ceq 3) {
+break
+} else {
+continue
+}
+}
+echo done
+", powershell);
+
+        // compared with what bash prints where PowerShell is installed
+        if let Ok(output) = std::process::Command::new("pwsh").args(["-NoProfile", "-Command", &powershell]).output() {
+            assert_eq!("1\n2\ndone\n", String::from_utf8_lossy(&output.stdout), "{powershell}");
+        }
+    }
+
+    #[test]
+    fn wildcard_first() {
+        let code = "
+var x = 1
+match x {
+    * => $echo any
+    1 => $echo one
+}
+";
+        let bash = super::transpile(super::TranspilerTarget::Bash, code);
+        let output = std::process::Command::new("bash").arg("-c").arg(&bash).output().unwrap();
+        assert_eq!("any\n", String::from_utf8_lossy(&output.stdout), "{bash}");
+
+        // the arms after a wildcard never match, as in a `case` of bash
+        let powershell = super::transpile(super::TranspilerTarget::PowerShell, code);
+        pretty_assertions::assert_eq!("$x = 1\necho any\n", powershell);
+
+        if let Ok(output) = std::process::Command::new("pwsh").args(["-NoProfile", "-Command", &powershell]).output() {
+            assert_eq!("any\n", String::from_utf8_lossy(&output.stdout), "{powershell}");
+        }
+    }
+}
+
+mod fish {
+    transpile_golden!(variables, Fish, "fish", "fish");
+    transpile_golden!(functions, Fish, "fish", "fish");
+    transpile_golden!(control_flow, Fish, "fish", "fish");
+    transpile_golden!(match_switch, Fish, "fish", "fish");
+    transpile_golden!(commands, Fish, "fish", "fish");
+    transpile_golden!(indented, Fish, "fish", "fish", super::TranspilerOptions { indent: lang_engine::transpiler::writer::Indent::Spaces(4), ..Default::default() });
+
+    #[test]
+    fn discarded_values() {
+        let code = "
+var x = 1
+if x == 1 {
+    \"one\"
+} else {
+    \"#{$date()}\"
+}
+{
+    x + 1
+}
+        ";
+
+        pretty_assertions::assert_eq!("\
+set x 1
+if test \"$x\" -eq 1
+else
+true \"$(date)\"
+end
+begin
+end
+", super::transpile(super::TranspilerTarget::Fish, code));
+    }
+
+    #[test]
+    fn unsupported() {
+        use lang_engine::transpiler::error::TranspilerErrorKind;
+
+        let code = "
+var a = \"a\"
+if a < \"b\": $echo less
+for file in \"src/?.rs\" {
+    $echo #{file}
+}
+match a {
+    \"a*\" => $echo star
+}
+        ";
+
+        for result in &super::transpile_errors(super::TranspilerTarget::Fish, code)[1..] {
+            assert!(matches!(result, Err(TranspilerErrorKind::Unsupported { .. })), "{result:?}");
+        }
+    }
+}
+
+mod custom_target {
+    use lang_engine::{parser::ast::{Expression, Statement}, transpiler::{error::{TranspilerErrorKind, TranspilerResult}, BashTranspiler, Capabilities, Target, TranspilerContext, TranspilerImpl, TranspilerTarget}};
+
+    /// A restricted shell, which is sh without commands
+    struct Restricted;
+
+    impl Target for Restricted {
+        fn name(&self) -> &str {
+            "rsh"
+        }
+
+        fn create<'a>(&self, ctx: TranspilerContext) -> Box<dyn TranspilerImpl<'a>> {
+            Box::new(RestrictedTranspiler(BashTranspiler::posix(ctx)))
+        }
+    }
+
+    struct RestrictedTranspiler(BashTranspiler);
+
+    impl<'a> TranspilerImpl<'a> for RestrictedTranspiler {
+        fn ctx(&self) -> &TranspilerContext {
+            self.0.ctx()
+        }
+
+        fn ctx_mut(&mut self) -> &mut TranspilerContext {
+            self.0.ctx_mut()
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            self.0.capabilities()
+        }
+
+        fn transpile_stmt(&mut self, statement: &'a Statement) -> TranspilerResult<String> {
+            match statement {
+                Statement::Expression(expr) if matches!(expr.value, Expression::ShellCommand(_)) => {
+                    Err(TranspilerErrorKind::unsupported("running a command").at(expr))
+                },
+                statement => self.0.transpile_stmt(statement),
+            }
+        }
+
+        fn transpile_expr(&mut self, expression: &'a Expression) -> TranspilerResult<String> {
+            self.0.transpile_expr(expression)
+        }
+    }
+
+    #[test]
+    fn custom_target() {
+        let target = TranspilerTarget::custom(Restricted);
+        assert_eq!("Custom(\"rsh\")", format!("{target:?}"));
+
+        pretty_assertions::assert_eq!("x=1\n", super::transpile(target, "var x = 1\n$rm -rf /\n"));
+    }
+
+    #[test]
+    fn capabilities() {
+        let posix = BashTranspiler::posix(TranspilerContext::default());
+        let bash = BashTranspiler::create(TranspilerContext::default());
+
+        assert!(!posix.capabilities().arrays);
+        assert!(bash.capabilities().arrays);
+    }
+}
+
+mod output {
+    use lang_engine::{error::EngineErrorKind, transpiler::{Target, TranspilerOptions, TranspilerTarget}, Engine};
+
+    #[test]
+    fn engine_writes_to_sink() {
+        let code = "\
+var name = \"world\"
+$echo hello #{name}
+";
+
+        let mut out = Vec::new();
+        let status = Engine::create_with(TranspilerTarget::Posix, TranspilerOptions::default()).exec(code, &mut out).unwrap();
+
+        assert_eq!(0, status);
+        assert_eq!(super::transpile(TranspilerTarget::Posix, code), String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn engine_fails_on_errors() {
+        let mut out = Vec::new();
+        let result = Engine::create_with(TranspilerTarget::Bash, TranspilerOptions::default()).exec("var a = 1\nbreak\n", &mut out);
+
+        assert!(matches!(result, Err(EngineErrorKind::ExecError)), "{result:?}");
+        // nothing of a failed script is written
+        assert_eq!("", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn default_prelude() {
+        let out = super::exec(TranspilerTarget::Bash, TranspilerTarget::Bash.options(), "var a = 1\n").unwrap();
+        assert_eq!("\
+#!/usr/bin/env bash
+# generated from virtual, edit that file instead of this one
+set -euo pipefail
+
+a=1
+", out);
+
+        let options = TranspilerOptions {
+            shebang: None,
+            header: false,
+            safe_ifs: true,
+            ..TranspilerTarget::Posix.options()
+        };
+        assert_eq!("set -eu\nIFS='\n\t'\n\na=1\n", super::exec(TranspilerTarget::Posix, options, "var a = 1\n").unwrap());
+
+        let out = super::exec(TranspilerTarget::PowerShell, TranspilerTarget::PowerShell.options(), "var a = 1\n").unwrap();
+        assert!(out.starts_with("#!/usr/bin/env pwsh\n"), "{out}");
+        assert!(out.contains("$ErrorActionPreference = 'Stop'\n"), "{out}");
+
+        let out = super::exec(TranspilerTarget::Fish, TranspilerTarget::Fish.options(), "var a = 1\n").unwrap();
+        assert!(out.starts_with("#!/usr/bin/env fish\n# generated from virtual"), "{out}");
+    }
+
+    #[test]
+    fn strict_mode() {
+        let code = "\
+var total = 0
+total += 0
+$echo #{total}
+$false
+$echo unreachable
+";
+
+        for (target, shell) in [(TranspilerTarget::Bash, "bash"), (TranspilerTarget::Posix, "sh")] {
+            let options = target.options();
+            let script = super::exec(target, options, code).unwrap();
+            let output = std::process::Command::new(shell).arg("-c").arg(&script).output().unwrap();
+
+            assert!(!output.status.success(), "{script}");
+            assert_eq!("0\n", String::from_utf8_lossy(&output.stdout), "{script}");
+        }
+
+        let options = TranspilerOptions { strict: true, ..Default::default() };
+        let result = super::exec(TranspilerTarget::Fish, options, code);
+        assert!(matches!(result, Err(EngineErrorKind::ExecError)), "{result:?}");
+    }
+}
+
+mod source_map {
+    use lang_engine::{cursor::Cursor, transpiler::{source_map::SourceMap, TranspilerOptions, TranspilerTarget}, Engine};
+
+    fn exec(code: &str) -> (String, SourceMap) {
+        let options = TranspilerOptions { source_map: true, ..Default::default() };
+        let mut engine = Engine::create_with(TranspilerTarget::Bash, options);
+
+        let out = super::exec_in(&mut engine, code).unwrap();
+        (out, engine.source_map().unwrap().clone())
+    }
+
+    #[test]
+    fn nested_statements() {
+        let code = "\
+var name = \"world\"
+for i in 0..2 {
+    if i == 1 {
+        $echo #{name}
+    }
+}
+";
+
+        let (out, map) = exec(code);
+        assert_eq!("\
+name='world'
+for (( i = 0; i < 2; i++ )); do
+if (( i == 1 )); then
+echo \"${name}\"
+fi
+done
+", out);
+
+        let lines: Vec<_> = (1..=6).map(|line| map.lookup(line).map(|span| span.start.line())).collect();
+        assert_eq!(vec![Some(1), Some(2), Some(3), Some(4), Some(4), Some(4)], lines);
+    }
+
+    #[test]
+    fn statement_headers() {
+        let code = "\
+@include \"lib/utils.tsh\"
+var count: Int = 1
+fn boom() {
+    $false
+}
+";
+
+        let (out, map) = exec(code);
+        assert_eq!("source 'lib/utils.sh'\ncount=1\nboom() {\nfalse\n}\n", out);
+
+        let starts: Vec<_> = (1..=5).map(|line| map.lookup(line).map(|span| span.start)).collect();
+        assert_eq!(vec![
+            Some(Cursor::from(1, 1)),
+            Some(Cursor::from(2, 1)),
+            Some(Cursor::from(3, 1)),
+            Some(Cursor::from(4, 5)),
+            Some(Cursor::from(4, 5)),
+        ], starts);
+    }
+
+    #[test]
+    fn written_and_parsed() {
+        let (_, map) = exec("var a = 1\n$echo #{a}\n");
+
+        let mut text = Vec::new();
+        map.write_to(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+
+        assert_eq!("tsh-source-map 1\nsource virtual\n1 1:1 1:10\n2 2:1 2:11\n", text);
+        assert_eq!(map, SourceMap::parse(&text).unwrap());
+        assert_eq!(Some(Cursor::from(2, 1)), map.lookup(2).map(|span| span.start));
+        assert!(SourceMap::parse("source virtual\n").is_err());
+        assert!(SourceMap::parse("tsh-source-map 1\nsource virtual\n1 1:9\n").is_err());
+    }
+
+    #[test]
+    fn marks_in_strings_kept() {
+        let (out, map) = exec("var a = \"\u{F8F0}0\u{F8F1}\"\n$echo \"#{a}\u{F8F0}x\" // \u{F8F1}\n");
+
+        assert_eq!("a='\u{F8F0}0\u{F8F1}'\necho \"${a}\u{F8F0}x\"\n", out);
+        assert_eq!(vec![Some(1), Some(2)], (1..=2).map(|line| map.lookup(line).map(|span| span.start.line())).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn recorded_only_when_asked() {
+        let mut engine = Engine::create_with(TranspilerTarget::Bash, TranspilerOptions::default());
+        engine.exec("var a = 1\n", &mut Vec::new()).unwrap();
+
+        assert!(engine.source_map().is_none());
+    }
+}
+
+mod debug {
+    use lang_engine::{error::EngineErrorKind, transpiler::{TranspilerOptions, TranspilerTarget}};
+
+    const CODE: &str = "\
+var name = \"world\"
+fn greet(who: String) {
+    $echo hello #{who}
+}
+greet(name)
+$false
+";
+
+    fn exec(target: TranspilerTarget, debug: bool) -> Result<String, EngineErrorKind> {
+        super::exec(target, TranspilerOptions { debug, ..Default::default() }, CODE)
+    }
+
+    #[test]
+    fn traced_in_source_lines() {
+        let script = exec(TranspilerTarget::Bash, true).unwrap();
+        let output = std::process::Command::new("bash").arg("-c").arg(&script).output().unwrap();
+        let trace = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!("hello world\n", String::from_utf8_lossy(&output.stdout));
+        assert!(trace.contains("+ virtual:1: name=world\n"), "{trace}");
+        assert!(trace.contains("+ virtual:5: greet world\n"), "{trace}");
+        assert!(trace.contains("+ virtual:3: echo hello world\n"), "{trace}");
+        assert!(trace.contains("virtual:6: failed with status 1\n"), "{trace}");
+    }
+
+    #[test]
+    fn minified_in_source_lines() {
+        let options = TranspilerOptions { debug: true, minify: true, ..Default::default() };
+        let script = super::exec(TranspilerTarget::Bash, options, CODE).unwrap();
+
+        let output = std::process::Command::new("bash").arg("-c").arg(&script).output().unwrap();
+        let trace = String::from_utf8_lossy(&output.stderr);
+
+        assert!(trace.contains("+ virtual:1: a=world\n"), "{trace}");
+        assert!(trace.contains("virtual:6: failed with status 1\n"), "{trace}");
+    }
+
+    #[test]
+    fn release_unchanged() {
+        assert_eq!(super::transpile(TranspilerTarget::Bash, CODE), exec(TranspilerTarget::Bash, false).unwrap());
+
+        let script = exec(TranspilerTarget::Bash, true).unwrap();
+        assert!(script.ends_with(&super::transpile(TranspilerTarget::Bash, CODE)), "{script}");
+    }
+
+    #[test]
+    fn bash_only() {
+        for target in [TranspilerTarget::Posix, TranspilerTarget::Fish, TranspilerTarget::PowerShell] {
+            let result = exec(target, true);
+            assert!(matches!(result, Err(EngineErrorKind::ExecError)), "{result:?}");
+        }
+    }
+}
+
+mod errors {
+    use lang_engine::{component::ComponentErrors, cursor::Cursor, error::SourceFile, lexer::Lexer, parser::Parser, transpiler::{error::TranspilerErrorKind, types::ValueType, Transpiler, TranspilerOptions, TranspilerTarget}};
+
+    #[test]
+    fn type_mismatch() {
+        let code = "\
+fn repeat(text: String, times: Int) {
+    $echo #{text} #{times}
+}
+var count: Int = \"three\"
+repeat(\"a\", \"b\")
+var a: Int = 1
+var total = a - \"b\"
+repeat(\"a\", 2)
+";
+
+        let results = super::transpile_errors(TranspilerTarget::Bash, code);
+        assert!(results[0].is_ok());
+
+        let result = &results[1];
+        assert!(matches!(result, Err(TranspilerErrorKind::TypeMismatch { expected: ValueType::Integer, found: ValueType::String, .. })), "{result:?}");
+
+        let Err(err) = &results[2] else {
+            panic!("{:?}", results[2]);
+        };
+        let span = err.span().expect("the error is placed at the argument");
+        assert_eq!((Cursor::from(5, 13), Cursor::from(5, 16)), (span.start, span.end));
+
+        assert!(results[3].is_ok());
+        let result = &results[4];
+        assert!(matches!(result, Err(TranspilerErrorKind::TypeMismatch { .. })), "{result:?}");
+
+        assert_eq!("repeat 'a' 2\n", results[5].as_ref().unwrap());
+    }
+
+    #[test]
+    fn collected_with_spans() {
+        let code = "\
+var range = 0..3
+while true {
+    break
+}
+continue
+";
+
+        let results = super::transpile_errors(TranspilerTarget::Bash, code);
+        assert!(results[1].is_ok(), "{:?}", results[1]);
+
+        let Err(err) = &results[0] else {
+            panic!("{:?}", results[0]);
+        };
+        assert!(matches!(err, TranspilerErrorKind::Unsupported { .. }), "{err:?}");
+        let span = err.span().unwrap();
+        assert_eq!((Cursor::from(1, 13), Cursor::from(1, 17)), (span.start, span.end));
+
+        let Err(err) = &results[2] else {
+            panic!("{:?}", results[2]);
+        };
+        assert!(matches!(err, TranspilerErrorKind::InvalidBreak { keyword: "continue", .. }), "{err:?}");
+        assert_eq!(Cursor::from(5, 1), err.span().unwrap().start);
+    }
+
+    #[test]
+    fn redeclared() {
+        let code = "\
+var name = 1
+fn greet(name: String) {
+    var greeting = \"hi\"
+    $echo #{greeting} #{name}
+}
+while true {
+    var name = 2
+    break
+}
+var name = 3
+";
+
+        let source_file = SourceFile::from(code.to_string(), Some(String::from("names.tsh")));
+        let mut lexer = Lexer::create(&source_file);
+        let mut parser = Parser::create(lexer.tokens(), &source_file);
+
+        let mut transpiler = Transpiler::create_with(&TranspilerTarget::Bash, TranspilerOptions::default(), parser.parse(), &source_file);
+        transpiler.transpile();
+
+        // collected errors are placed at their statement and show the part of the source it was parsed from
+        let errors = transpiler.fetch_errors();
+        assert_eq!(1, errors.len(), "{errors:#?}");
+        assert!(matches!(&*errors[0].kind, TranspilerErrorKind::Redeclared { name, .. } if name == "name"), "{errors:#?}");
+        assert_eq!((Cursor::from(10, 1), Cursor::from(10, 13)), (errors[0].start, errors[0].end));
+        assert_eq!("var name = 3", errors[0].source_file.get_code());
+
+        let rendered = errors[0].to_string();
+        assert!(rendered.contains("is declared twice in the same scope"), "{rendered}");
+        assert!(rendered.contains("names.tsh"), "{rendered}");
+    }
+}
+
+mod layout {
+    use lang_engine::transpiler::{TranspilerOptions, TranspilerTarget, writer::Indent};
+
+    const CODE: &str = "
+var greeting = \"hello\\nworld\"
+
+fn greet(name: String) {
+    for i in 0..2 {
+        if i == 0 {
+            $echo #{greeting}
+        } else {
+            $echo #{name}
+        }
+    }
+}
+fn kind(x: Int) {
+    match x {
+        1 => \"one\"
+        * => {
+            var y = x
+            \"many\"
+        }
+    }
+}
+
+
+greet(\"bob\")
+";
+
+    fn transpile(target: TranspilerTarget, indent: Indent, blank_lines: bool) -> String {
+        super::transpile_with(target, TranspilerOptions { indent, blank_lines, ..Default::default() }, CODE)
+    }
+
+    #[test]
+    fn indented_with_spaces() {
+        pretty_assertions::assert_eq!("\
+greeting='hello
+world'
+
+greet() {
+  local name=\"$1\"
+  for (( i = 0; i < 2; i++ )); do
+    if (( i == 0 )); then
+      echo \"${greeting}\"
+    else
+      echo \"${name}\"
+    fi
+  done
+}
+kind() {
+  local x=\"$1\"
+  case \"$x\" in
+    1)
+      echo 'one'
+      ;;
+    *)
+      local y=\"$x\"
+      echo 'many'
+      ;;
+  esac
+}
+
+greet 'bob'
+", transpile(TranspilerTarget::Bash, Indent::Spaces(2), true));
+    }
+
+    #[test]
+    fn indented_with_tabs() {
+        let script = transpile(TranspilerTarget::Bash, Indent::Tabs, false);
+
+        assert!(script.contains("\n\tfor (( i = 0; i < 2; i++ )); do\n\t\tif (( i == 0 )); then\n\t\t\techo"), "{script}");
+        assert!(script.starts_with("greeting='hello\nworld'\ngreet() {\n"), "{script}");
+    }
+
+    #[test]
+    fn flat_by_default() {
+        let script = transpile(TranspilerTarget::Bash, Indent::None, false);
+
+        assert!(script.lines().all(|line| !line.starts_with(' ')), "{script}");
+        assert_eq!(super::transpile(TranspilerTarget::Bash, CODE), script);
+    }
+
+    #[test]
+    fn runs_the_same() {
+        for (target, shell) in [(TranspilerTarget::Bash, "bash"), (TranspilerTarget::Posix, "sh")] {
+            let script = transpile(target, Indent::Spaces(4), true);
+            let output = std::process::Command::new(shell).arg("-c").arg(&script).output().unwrap();
+
+            assert_eq!("hello\nworld\nbob\n", String::from_utf8_lossy(&output.stdout), "{script}");
+        }
+    }
+
+    #[test]
+    fn here_document_not_indented() {
+        let code = "\
+fn count() {
+    for line in $printf(\"a\\nb\\n\") {
+        $echo #{line}
+    }
+}
+count()
+";
+        let options = TranspilerOptions { indent: Indent::Spaces(4), ..Default::default() };
+        let script = super::transpile_with(TranspilerTarget::Posix, options, code);
+        assert!(script.contains("        done <<EOF\n$__tsh_output\nEOF\n    fi\n"), "{script}");
+
+        let output = std::process::Command::new("sh").arg("-c").arg(&script).output().unwrap();
+        assert_eq!("a\nb\n", String::from_utf8_lossy(&output.stdout), "{script}");
+    }
+
+    #[test]
+    fn layout_characters_in_strings_kept() {
+        let code = "\
+if true {
+    $echo \"a\u{F8F2}b\u{F8F3}c\u{F8F4}d\u{F8F5}e\"
+}
+";
+        let options = TranspilerOptions { indent: Indent::Spaces(4), ..Default::default() };
+
+        for target in [TranspilerTarget::Bash, TranspilerTarget::Fish, TranspilerTarget::PowerShell] {
+            let script = super::transpile_with(target, options.clone(), code);
+            assert!(script.contains("a\u{F8F2}b\u{F8F3}c\u{F8F4}d\u{F8F5}e"), "{script}");
+        }
+
+        let script = super::transpile_with(TranspilerTarget::Bash, options, code);
+        assert!(script.ends_with("then\n    echo \"a\u{F8F2}b\u{F8F3}c\u{F8F4}d\u{F8F5}e\"\nfi\n"), "{script}");
+    }
+}
+
+mod comments {
+    use lang_engine::transpiler::{TranspilerOptions, TranspilerTarget};
+
+    const CODE: &str = "\
+// settings
+var target = \"prod\" // trailing
+
+/// Greets someone.
+////////////////
+fn greet(name: String, times = 2) {
+    /* a
+       block */
+    $echo #{name}
+}
+
+/// Whether it's live
+fn live(): Bool {
+    target == \"prod\"
+    // after the tail
+}
+// the end
+";
+
+    fn transpile(target: TranspilerTarget, comments: bool) -> String {
+        super::transpile_with(target, TranspilerOptions { comments, ..Default::default() }, CODE)
+    }
+
+    #[test]
+    fn kept() {
+        pretty_assertions::assert_eq!("\
+# settings
+target='prod'
+# trailing
+#//////////////
+# Usage: greet <name> [times]
+#
+# Greets someone.
+greet() {
+local name=\"$1\"
+local times=2
+if (( $# >= 2 )); then times=\"$2\"; fi
+# a
+#       block
+echo \"${name}\"
+}
+# Usage: live
+#
+# Whether it's live
+live() {
+[[ \"$target\" == 'prod' ]] && return 0
+return 1
+# after the tail
+}
+# the end
+", transpile(TranspilerTarget::Bash, true));
+    }
+
+    #[test]
+    fn dropped_by_default() {
+        let script = transpile(TranspilerTarget::Bash, false);
+
+        assert!(script.lines().all(|line| !line.starts_with('#')), "{script}");
+        assert_eq!(super::transpile(TranspilerTarget::Bash, CODE), script);
+    }
+
+    #[test]
+    fn same_in_every_shell() {
+        for target in [TranspilerTarget::Fish, TranspilerTarget::PowerShell] {
+            let script = transpile(target, true);
+
+            assert!(script.contains("# Usage: greet <name> [times]\n#\n# Greets someone.\n"), "{script}");
+            assert!(script.ends_with("# the end\n"), "{script}");
+        }
+    }
+}
+
+mod minify {
+    use lang_engine::transpiler::{Target, TranspilerOptions, TranspilerTarget};
+
+    const CODE: &str = "\
+// settings
+var target = \"prod\"
+var HOME_DIR = \"/tmp\"
+
+/// Greets someone
+fn greet(name: String, times = 2) {
+    var count = 0
+    for i in 0..times {
+        count += 1
+        $echo hi #{name} #{i}
+    }
+    $echo \"done #{count}\"
+}
+
+fn cleanup() {
+    $echo cleaning
+}
+
+fn is_live(): Bool {
+    target == \"prod\"
+}
+
+$trap cleanup EXIT
+greet(\"bob\")
+if is_live() {
+    $echo live #{HOME_DIR} $target
+}
+";
+
+    fn transpile(target: TranspilerTarget) -> String {
+        let options = TranspilerOptions { minify: true, ..target.options() };
+        super::transpile_with(target, options, CODE)
+    }
+
+    #[test]
+    fn shortened() {
+        pretty_assertions::assert_eq!("\
+#!/usr/bin/env bash
+set -euo pipefail
+target='prod'
+HOME_DIR='/tmp'
+_a() {
+local a=\"$1\"
+local b=2
+if (( $# >= 2 )); then b=\"$2\"; fi
+local c=0
+for (( d = 0; d < b; d++ )); do
+(( c += 1 )) || true
+echo hi \"${a}\" \"${d}\"
+done
+echo \"done ${c}\"
+}
+cleanup() {
+echo cleaning
+}
+_b() {
+[[ \"$target\" == 'prod' ]] && return 0
+return 1
+}
+trap cleanup EXIT
+_a 'bob'
+if _b; then
+echo live \"${HOME_DIR}\" $target
+fi
+", transpile(TranspilerTarget::Bash));
+    }
+
+    #[test]
+    fn runs_the_same() {
+        let script = transpile(TranspilerTarget::Bash);
+        let output = std::process::Command::new("bash").arg("-c").arg(&script).output().unwrap();
+
+        assert!(output.status.success(), "{script}\n{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!("hi bob 0\nhi bob 1\ndone 2\nlive /tmp prod\ncleaning\n", String::from_utf8_lossy(&output.stdout), "{script}");
+    }
+
+    #[test]
+    fn same_names_in_every_shell() {
+        for target in [TranspilerTarget::Fish, TranspilerTarget::PowerShell] {
+            let script = transpile(target);
+
+            assert!(script.contains("function _a") && script.contains("\n_a 'bob'\n"), "{script}");
+            assert!(script.contains("function cleanup") && script.contains("HOME_DIR"), "{script}");
+            assert!(script.lines().all(|line| !line.starts_with(' ') && !line.starts_with("# ")), "{script}");
+        }
+    }
+}
+
+mod posix {
+    use lang_engine::transpiler::TranspilerTarget;
+
+    /// Runs the bash and the sh output of a script and expects them to print the same
+    macro_rules! posix_differential {
+        ($name:ident, $code:literal) => {
+            #[test]
+            fn $name() {
+                let bash = run("bash", &super::transpile(TranspilerTarget::Bash, $code));
+                let sh = run("sh", &super::transpile(TranspilerTarget::Posix, $code));
+
+                assert!(!bash.is_empty());
+                pretty_assertions::assert_eq!(bash, sh);
+            }
+        };
+    }
+
+    fn run(shell: &str, script: &str) -> String {
+        let output = std::process::Command::new(shell)
+            .args(["-c", script])
+            .output()
+            .expect("shell should be installed");
+
+        assert!(output.status.success(), "{shell} failed:\n{script}\n{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    posix_differential!(
+        loops,
+        "
+var count = 0
+while count < 10 {
+    count += 1
+    if count == 2: continue
+}
+for i in 0..5 {
+    if i == 1: continue
+    if i > 3 {
+        break
+    }
+    $echo #{i}
+}
+for i in 1..=count - 7 {
+    count -= i
+}
+$echo #{count}
+for line in $printf(\"a b\\nc\\n\") {
+    $echo \"<#{line}>\"
+}
+for line in $true() {
+    $echo \"got <#{line}>\"
+}
+for file in \"/bin/s[h]\" {
+    $echo #{file}
+}
+        "
+    );
+
+    #[test]
+    fn glob_without_matches() {
+        let code = "for file in \"/nowhere/*.rs\" {\n    $echo #{file}\n}\n$echo done\n";
+
+        for (target, shell) in [(TranspilerTarget::Bash, "bash"), (TranspilerTarget::Posix, "sh")] {
+            assert_eq!("done\n", run(shell, &super::transpile(target, code)));
+        }
+    }
+
+    posix_differential!(
+        functions,
+        "
+fn greet(name: String, greeting = \"Hello\") {
+    $echo \"#{greeting}, #{name}\"
+}
+
+fn add(a: Int, b: Int): Int {
+    a + b
+}
+
+fn is_even(n: Int): Bool {
+    return n / 2 * 2 == n
+}
+
+fn sign(n: Int) {
+    if n < 0 {
+        \"negative\"
+    } else {
+        \"positive\"
+    }
+}
+
+greet(\"world\")
+greet(\"sh\", \"Hi\")
+var total = add(1, 2) * 2
+if is_even(total) && !is_even(add(total, 1)): $echo even
+$echo \"#{add(total, 1)} #{sign(total)} #{sign(-total)}\"
+
+fn escaped() {
+    \"a\\\\nb\"
+}
+escaped()
+        "
+    );
+
+    posix_differential!(
+        conditions,
+        "
+var name = \"tsh\"
+var empty = \"\"
+var level = 2
+if name == \"tsh\" && name != empty: $echo equal
+if empty {
+    $echo never
+} else {
+    $echo empty
+}
+var ready = level >= 10 || level < 0 && level != 5
+$echo #{ready}
+match level {
+    1 || 2 => $echo low
+    * => $echo unknown
+}
+var msg = match name {
+    \"tsh\" => \"matched\"
+    * => \"other\"
+}
+$echo #{msg}
+        "
+    );
+
+    #[test]
+    fn unsupported() {
+        use lang_engine::transpiler::error::TranspilerErrorKind;
+
+        let code = "
+var a = \"a\"
+for item in a {
+    $echo #{item}
+}
+if a < \"b\": $echo less
+        ";
+
+        for result in &super::transpile_errors(super::TranspilerTarget::Posix, code)[1..] {
+            assert!(matches!(result, Err(TranspilerErrorKind::Unsupported { .. })), "{result:?}");
+        }
+    }
+}